@@ -1,26 +1,88 @@
-use tauri::Manager;
+use tauri::{Manager, RunEvent};
 
-use crate::{ipc, settings, state, windows};
+use crate::{
+    autostart, bundle, cli, deeplink, instance, ipc, protocols, safe_mode, settings, splash, state,
+    theme_watcher, themes, tray, windows,
+};
+
+/// Starts the app for `launch`; `args` are forwarded as-is when another
+/// instance is already running.
+pub fn run(launch: cli::Options, args: &[String]) {
+    let listener = match instance::acquire(args) {
+        instance::Startup::Primary(listener) => listener,
+        instance::Startup::Forwarded => {
+            println!("Ghostcord Lite is already running; passed the launch on to it.");
+            return;
+        }
+        instance::Startup::Unreachable(err) => {
+            eprintln!("Ghostcord Lite is already running but did not respond ({err}).");
+            std::process::exit(1);
+        }
+    };
 
-pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_log::Builder::default().build())
         .manage(settings::SettingsStore::new())
         .manage(state::StateStore::new())
+        .manage(splash::SplashGate::default())
+        .manage(safe_mode::SafeMode::default())
+        .manage(bundle::PendingImport::default())
         .invoke_handler(ipc::handler())
-        .setup(|app| {
+        .register_asynchronous_uri_scheme_protocol(protocols::SCHEME, protocols::handler())
+        .setup(move |app| {
             settings::log_paths(app.handle());
+            if let Some(listener) = listener {
+                instance::serve(app.handle(), listener);
+            }
+            splash::open(app.handle());
+            splash::status(app.handle(), "Loading settings…");
             let state_store = app.state::<state::StateStore>();
             let _ = state::load_state(app.handle(), &state_store);
+            let store = app.state::<settings::SettingsStore>();
+            safe_mode::begin_launch(app.handle(), launch.safe_mode);
+            if launch.no_vencord {
+                store.disable_for_session(&["enable_vencord"]);
+            }
+            if let Some(profile) = &launch.profile {
+                if let Err(err) = settings::switch_profile(app.handle(), &store, profile) {
+                    log::warn!("Cannot start with profile {profile}: {err}");
+                }
+            }
 
-            let window = windows::create_main_window(app.handle())?;
+            splash::status(app.handle(), "Loading Discord…");
+            let window = windows::create_main_window(app.handle(), &launch)?;
             windows::restore_main_window_state(&window, &state_store);
             windows::attach_main_window_state_listeners(app.handle(), &window);
 
             ipc::apply_initial_config(app.handle())?;
+            let cfg = store.get();
+            if let Err(err) = tray::create(app.handle()) {
+                log::warn!("Tray icon unavailable: {err}");
+            }
+            if cfg.start_minimized || launch.start_minimized {
+                splash::start_minimized(app.handle());
+            }
+            // The binary may have moved since the entry was written.
+            if let Err(err) = autostart::sync(&cfg) {
+                log::warn!("Failed to update autostart entry: {err}");
+            }
+            if let Err(err) = deeplink::sync_protocol_handler(&cfg) {
+                log::warn!("Failed to update discord:// handler: {err}");
+            }
+            if !cfg.enable_vencord {
+                splash::assets_ready(app.handle());
+            }
+            ipc::watch_settings(app.handle());
+            theme_watcher::start(app.handle());
+            themes::refresh_remote_in_background(app.handle());
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error running Ghostcord Lite");
+        .build(tauri::generate_context!())
+        .expect("error building Ghostcord Lite")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                safe_mode::exiting(app);
+            }
+        });
 }
@@ -0,0 +1,185 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::AppConfig, persist};
+
+pub const DESKTOP_FILE: &str = "ghostcord-lite.desktop";
+pub const START_MINIMIZED_ARG: &str = "--start-minimized";
+
+/// An XDG base directory: the variable's value, or `fallback` under the home
+/// dir when it is unset or not absolute, as the base directory spec asks.
+pub fn xdg_dir_from(
+    value: Option<OsString>,
+    home: Option<OsString>,
+    fallback: &str,
+) -> Option<PathBuf> {
+    value
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| home.map(|home| PathBuf::from(home).join(fallback)))
+}
+
+/// `$XDG_CONFIG_HOME/autostart`, usually `~/.config/autostart`.
+fn autostart_dir_from(config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    xdg_dir_from(config_home, home, ".config").map(|dir| dir.join("autostart"))
+}
+
+pub fn autostart_dir() -> Option<PathBuf> {
+    autostart_dir_from(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"))
+}
+
+/// The binary to launch on login. AppImages run from a temporary mount, so
+/// the image itself is used instead.
+pub fn launch_path() -> Result<PathBuf, String> {
+    if let Some(appimage) = env::var_os("APPIMAGE").filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(appimage));
+    }
+    env::current_exe().map_err(|e| e.to_string())
+}
+
+/// Quotes one `Exec` argument per the desktop entry spec, then escapes it
+/// for the string value the key holds.
+pub fn quote_exec_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(',
+        ')', '`',
+    ];
+    let arg = arg.replace('%', "%%");
+    let quoted = if arg.contains(RESERVED) {
+        let mut quoted = String::from("\"");
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    } else {
+        arg
+    };
+    quoted
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+fn desktop_entry(exec: &Path, start_minimized: bool) -> String {
+    let mut command = quote_exec_arg(&exec.to_string_lossy());
+    if start_minimized {
+        command.push(' ');
+        command.push_str(START_MINIMIZED_ARG);
+    }
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Ghostcord Lite\n\
+         Comment=Start Ghostcord Lite on login\n\
+         Exec={command}\n\
+         Icon=ghostcord-lite\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n"
+    )
+}
+
+/// Writes or removes the entry in `dir`. Only touches the file when its
+/// contents would change.
+fn apply_in(dir: &Path, exec: &Path, enabled: bool, start_minimized: bool) -> Result<(), String> {
+    let path = dir.join(DESKTOP_FILE);
+    if !enabled {
+        return match fs::remove_file(&path) {
+            Ok(()) => {
+                log::info!("Removed autostart entry {}", path.display());
+                Ok(())
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(format!("failed to remove {}: {err}", path.display())),
+        };
+    }
+
+    let entry = desktop_entry(exec, start_minimized);
+    if fs::read_to_string(&path).is_ok_and(|current| current == entry) {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    persist::write_atomic(&path, entry.as_bytes())?;
+    log::info!("Wrote autostart entry {}", path.display());
+    Ok(())
+}
+
+/// Brings the login entry in line with `autostart`/`autostart_minimized`.
+pub fn sync(cfg: &AppConfig) -> Result<(), String> {
+    if !cfg!(target_os = "linux") {
+        if cfg.autostart {
+            return Err("autostart is only supported on Linux".to_string());
+        }
+        return Ok(());
+    }
+    let dir = autostart_dir().ok_or("neither XDG_CONFIG_HOME nor HOME is set")?;
+    apply_in(&dir, &launch_path()?, cfg.autostart, cfg.autostart_minimized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_xdg(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ghostcord-xdg-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn autostart_dir_prefers_absolute_xdg_config_home() {
+        assert_eq!(
+            autostart_dir_from(Some("/xdg".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/xdg/autostart"))
+        );
+        assert_eq!(
+            autostart_dir_from(Some("relative".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/home/me/.config/autostart"))
+        );
+        assert_eq!(
+            autostart_dir_from(None, Some("/home/me".into())),
+            Some(PathBuf::from("/home/me/.config/autostart"))
+        );
+        assert_eq!(autostart_dir_from(Some("".into()), None), None);
+    }
+
+    #[test]
+    fn writes_and_removes_entry() {
+        let xdg = temp_xdg("toggle");
+        let dir = autostart_dir_from(Some(xdg.clone().into()), None).unwrap();
+        let path = dir.join(DESKTOP_FILE);
+
+        apply_in(&dir, Path::new("/opt/ghostcord/ghostcord-lite"), true, true).unwrap();
+        let entry = fs::read_to_string(&path).unwrap();
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("\nExec=/opt/ghostcord/ghostcord-lite --start-minimized\n"));
+
+        apply_in(&dir, Path::new("/opt/ghostcord/ghostcord-lite"), true, false).unwrap();
+        let entry = fs::read_to_string(&path).unwrap();
+        assert!(entry.contains("\nExec=/opt/ghostcord/ghostcord-lite\n"));
+
+        apply_in(&dir, Path::new("/opt/ghostcord/ghostcord-lite"), false, false).unwrap();
+        assert!(!path.exists());
+        // Removing again is not an error.
+        apply_in(&dir, Path::new("/opt/ghostcord/ghostcord-lite"), false, false).unwrap();
+
+        let _ = fs::remove_dir_all(&xdg);
+    }
+
+    #[test]
+    fn exec_paths_are_quoted() {
+        assert_eq!(quote_exec_arg("/usr/bin/ghostcord"), "/usr/bin/ghostcord");
+        assert_eq!(
+            quote_exec_arg("/home/me/My Apps/ghostcord"),
+            "\"/home/me/My Apps/ghostcord\""
+        );
+        assert_eq!(quote_exec_arg("/opt/100%/a$b"), "\"/opt/100%%/a\\\\$b\"");
+    }
+}
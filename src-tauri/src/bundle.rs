@@ -0,0 +1,340 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    config::{AppConfig, FieldError, LoadWarning},
+    persist,
+    settings::{self, FieldChange, SettingsChange},
+    state::{self, AppState},
+    themes,
+    vencord::{self, VencordSnapshot},
+};
+
+const BUNDLE_FORMAT: &str = "ghostcord-bundle";
+const BUNDLE_VERSION: u32 = 2;
+
+/// A portable copy of a Ghostcord setup in a single JSON file. Settings and
+/// state are stored in their versioned on-disk form so the usual migrations
+/// run on import.
+#[derive(Debug, Serialize, Deserialize)]
+struct Bundle {
+    format: String,
+    bundle_version: u32,
+    exported_at: u64,
+    app_version: String,
+    settings: Value,
+    #[serde(default)]
+    state: Option<Value>,
+    #[serde(default)]
+    themes: Vec<BundleTheme>,
+    /// Version 1 bundles carried at most one theme.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    theme: Option<BundleTheme>,
+    #[serde(default)]
+    vencord: Option<VencordSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleTheme {
+    file_name: String,
+    css: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundlePreview {
+    pub app_version: String,
+    pub exported_at: u64,
+    pub changes: Vec<FieldChange>,
+    pub has_state: bool,
+    pub theme_files: Vec<String>,
+    pub vencord: Option<VencordSnapshot>,
+    pub warnings: Vec<LoadWarning>,
+    pub errors: Vec<FieldError>,
+}
+
+/// The bundle picked for the last preview, waiting to be imported.
+#[derive(Default)]
+pub struct PendingImport(Mutex<Option<PathBuf>>);
+
+impl PendingImport {
+    pub fn set(&self, path: PathBuf) {
+        *self.0.lock().unwrap() = Some(path);
+    }
+
+    pub fn take(&self) -> Option<PathBuf> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// A bundle turned into the config that importing it would save.
+struct PreparedImport {
+    bundle: Bundle,
+    config: AppConfig,
+    theme_files: Vec<(PathBuf, String)>,
+    warnings: Vec<LoadWarning>,
+}
+
+pub fn export(app: &AppHandle, path: &Path, include_state: bool) -> Result<(), String> {
+    let mut cfg = app.state::<settings::SettingsStore>().saved();
+
+    // Themes travel as library files, whatever path they had here.
+    let dir = themes::themes_dir(app)?;
+    let mut bundled = Vec::new();
+    cfg.themes.retain_mut(|theme| {
+        // URL themes are downloaded again on the other end.
+        if theme.url.is_some() {
+            return true;
+        }
+        let path = themes::theme_path(&dir, theme);
+        let css = match fs::read_to_string(&path) {
+            Ok(css) => css,
+            Err(err) => {
+                log::warn!("Theme {} not exported: {err}", path.display());
+                return false;
+            }
+        };
+        let Some(file_name) = themes::safe_file_name(&theme.file) else {
+            return false;
+        };
+        theme.file = file_name.clone();
+        bundled.push(BundleTheme { file_name, css });
+        true
+    });
+
+    let state = if include_state {
+        Some(app.state::<state::StateStore>().get().to_versioned()?)
+    } else {
+        None
+    };
+
+    let bundle = Bundle {
+        format: BUNDLE_FORMAT.to_string(),
+        bundle_version: BUNDLE_VERSION,
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        app_version: app.package_info().version.to_string(),
+        vencord: Some(vencord::snapshot(app, cfg.vencord_pin.as_deref())?),
+        settings: cfg.to_versioned()?,
+        state,
+        themes: bundled,
+        theme: None,
+    };
+
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    persist::write_atomic(path, json.as_bytes())?;
+    log::info!("Exported config bundle to {}", path.display());
+    Ok(())
+}
+
+fn read_bundle(path: &Path) -> Result<Bundle, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse_bundle(&contents)
+}
+
+fn parse_bundle(contents: &str) -> Result<Bundle, String> {
+    let bundle = serde_json::from_str::<Bundle>(contents)
+        .map_err(|e| format!("not a Ghostcord bundle: {e}"))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(format!("not a Ghostcord bundle: format is {}", bundle.format));
+    }
+    if bundle.bundle_version > BUNDLE_VERSION {
+        return Err(format!(
+            "bundle version {} is newer than supported {BUNDLE_VERSION}",
+            bundle.bundle_version
+        ));
+    }
+    Ok(bundle)
+}
+
+fn prepare(app: &AppHandle, path: &Path) -> Result<PreparedImport, String> {
+    let bundle = read_bundle(path)?;
+    let loaded = AppConfig::from_versioned(bundle.settings.clone());
+    let mut config = loaded.config;
+    let current = app.state::<settings::SettingsStore>().saved();
+
+    // Tokens stay on the machine they were set on.
+    config.vencord_source.auth_token = current.vencord_source.auth_token.clone();
+
+    // A pin on another machine's install id means nothing here; fall back
+    // to the release tag it was recorded as.
+    if let Some(pin) = config.vencord_pin.clone() {
+        if !vencord::is_installed(app, &pin) {
+            config.vencord_pin = bundle
+                .vencord
+                .as_ref()
+                .and_then(|snapshot| snapshot.pinned_tag.clone())
+                .or(Some(pin));
+        }
+    }
+
+    // Point each entry at the library copy the import will write. Entries of
+    // version 1 bundles still hold the exporting machine's path.
+    let dir = themes::themes_dir(app)?;
+    let mut theme_files = Vec::new();
+    for theme in bundle.themes.iter().chain(&bundle.theme) {
+        let file_name = themes::safe_file_name(&theme.file_name)
+            .ok_or_else(|| format!("invalid theme file name {}", theme.file_name))?;
+        let taken = |name: &str| {
+            let path = dir.join(name);
+            theme_files.iter().any(|(other, _): &(PathBuf, String)| *other == path)
+                || (path.exists() && !fs::read_to_string(&path).is_ok_and(|css| css == theme.css))
+        };
+        let file_name = free_file_name(&file_name, taken);
+        for entry in config.themes.iter_mut().filter(|entry| entry.url.is_none()) {
+            if Path::new(&entry.file).file_name() == Some(theme.file_name.as_ref()) {
+                entry.file = file_name.clone();
+            }
+        }
+        theme_files.push((dir.join(file_name), theme.css.clone()));
+    }
+
+    Ok(PreparedImport {
+        bundle,
+        config,
+        theme_files,
+        warnings: loaded.warnings,
+    })
+}
+
+/// `name`, or `name` with `-2`, `-3`, ... before its extensions when it is
+/// taken, so an import never overwrites a different library theme.
+fn free_file_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(name) {
+        return name.to_string();
+    }
+    let (stem, extensions) = name.split_at(name.find('.').unwrap_or(name.len()));
+    (2..)
+        .map(|n| format!("{stem}-{n}{extensions}"))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_default()
+}
+
+pub fn preview(app: &AppHandle, path: &Path) -> Result<BundlePreview, String> {
+    let prepared = prepare(app, path)?;
+    let current = app.state::<settings::SettingsStore>().saved();
+
+    Ok(BundlePreview {
+        app_version: prepared.bundle.app_version,
+        exported_at: prepared.bundle.exported_at,
+        changes: SettingsChange::between(&current, &prepared.config).changed,
+        has_state: prepared.bundle.state.is_some(),
+        theme_files: prepared
+            .theme_files
+            .iter()
+            .map(|(target, _)| target.to_string_lossy().to_string())
+            .collect(),
+        vencord: prepared.bundle.vencord,
+        warnings: prepared.warnings,
+        errors: prepared.config.validate(),
+    })
+}
+
+/// Imported state with what belongs to this machine kept: window bounds,
+/// the active profile, and the launch record safe mode and rollbacks go by.
+fn merge_state(current: AppState, imported: AppState) -> AppState {
+    AppState {
+        window_bounds: current.window_bounds,
+        active_profile: current.active_profile,
+        launch_count: current.launch_count,
+        last_finished_launch: current.last_finished_launch,
+        recovery_pin: current.recovery_pin,
+        ..imported
+    }
+}
+
+/// Applies a bundle to the active profile. Window bounds, the active
+/// profile and the launch record are machine specific and stay as they are.
+pub fn import(app: &AppHandle, path: &Path, include_state: bool) -> Result<AppConfig, String> {
+    let prepared = prepare(app, path)?;
+
+    for (target, css) in &prepared.theme_files {
+        persist::write_atomic(target, css.as_bytes())?;
+    }
+
+    let mut config = prepared.config;
+    themes::adopt_pending_css(app, &mut config);
+    let store = app.state::<settings::SettingsStore>();
+    let cfg = settings::save_settings(app, &store, config)?;
+
+    if include_state {
+        if let Some(value) = prepared.bundle.state {
+            let (imported, _) = AppState::from_versioned(value)?;
+            let state_store = app.state::<state::StateStore>();
+            let merged = merge_state(state_store.get(), imported);
+            state::save_state(app, &state_store, merged)?;
+        }
+    }
+
+    log::info!("Imported config bundle from {}", path.display());
+    Ok(cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn bundle(format: &str, version: u32) -> String {
+        json!({
+            "format": format,
+            "bundle_version": version,
+            "exported_at": 0,
+            "app_version": "0.1.0",
+            "settings": {},
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn bundles_are_checked_for_format_and_version() {
+        assert!(parse_bundle(&bundle(BUNDLE_FORMAT, 1)).is_ok());
+        assert!(parse_bundle(&bundle(BUNDLE_FORMAT, BUNDLE_VERSION)).is_ok());
+
+        let err = parse_bundle(&bundle(BUNDLE_FORMAT, BUNDLE_VERSION + 1)).unwrap_err();
+        assert!(err.contains("newer than supported"), "{err}");
+        let err = parse_bundle(&bundle("something-else", 1)).unwrap_err();
+        assert!(err.contains("format is something-else"), "{err}");
+        assert!(parse_bundle("{}").is_err());
+    }
+
+    #[test]
+    fn imported_state_keeps_this_machines_launch_record() {
+        let current = AppState {
+            launch_count: 10,
+            last_finished_launch: 10,
+            ..AppState::default()
+        };
+        let imported = AppState {
+            last_active_channel: Some("123".to_string()),
+            launch_count: 7,
+            last_finished_launch: 4,
+            recovery_pin: Some("v1.10.0".to_string()),
+            ..AppState::default()
+        };
+        let merged = merge_state(current, imported);
+        assert_eq!(merged.failed_launches(), 0);
+        assert_eq!(merged.launch_count, 10);
+        assert!(merged.recovery_pin.is_none());
+        assert_eq!(merged.last_active_channel.as_deref(), Some("123"));
+    }
+
+    #[test]
+    fn imported_themes_get_a_free_name() {
+        let taken = ["dark.theme.css", "dark-2.theme.css", "plain"];
+        let free = |name| free_file_name(name, |candidate| taken.contains(&candidate));
+        assert_eq!(free("light.theme.css"), "light.theme.css");
+        assert_eq!(free("dark.theme.css"), "dark-3.theme.css");
+        assert_eq!(free("plain"), "plain-2");
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    env,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::{
+    autostart,
+    config::AppConfig,
+    deeplink, migrate,
+    settings::{self, APP_IDENTIFIER},
+    vencord::{self, VencordProgress},
+};
+
+const USAGE: &str = "\
+Usage: ghostcord-lite [OPTIONS] [LINK]
+       ghostcord-lite [OPTIONS] <COMMAND>
+
+Options:
+      --start-minimized   Start hidden in the tray
+      --profile <NAME>    Use a settings profile
+      --no-vencord        Run without Vencord this time
+      --safe-mode         Run without themes, CSS, blockers or Vencord this time
+      --data-dir <PATH>   Keep settings, downloads and web data in PATH
+      --devtools          Open the developer tools
+  -h, --help              Print this help
+  -V, --version           Print the version
+
+Commands (these exit without opening a window):
+  config get [KEY]        Print the settings, or one dotted KEY
+  config set KEY VALUE    Change a setting; VALUE is JSON or a plain string
+  vencord status          List the installed Vencord versions
+  vencord update          Download the latest (or pinned) Vencord release
+  paths                   Print where Ghostcord keeps its files
+
+LINK is a discord:// or Discord web link to open. Settings changed with
+`config set` apply the next time Ghostcord starts.
+";
+
+/// What the command line asks of a launch. A running instance only acts on
+/// the route, profile, `start_minimized` and `devtools` of later launches.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    /// App route from a deep link, see `deeplink::parse`.
+    pub route: Option<String>,
+    pub profile: Option<String>,
+    pub start_minimized: bool,
+    pub no_vencord: bool,
+    pub safe_mode: bool,
+    pub data_dir: Option<PathBuf>,
+    pub devtools: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Start the app, or hand the launch to the running instance.
+    Run,
+    Help,
+    Version,
+    ConfigGet(Option<String>),
+    ConfigSet(String, String),
+    VencordStatus,
+    VencordUpdate,
+    Paths,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub options: Options,
+    pub command: Command,
+}
+
+/// Reads `args` as passed to the process, program name included.
+pub fn parse(args: &[String]) -> Result<Cli, String> {
+    let mut options = Options::default();
+    let mut help = false;
+    let mut version = false;
+    let mut words: Vec<&str> = Vec::new();
+
+    let mut rest = args.iter().skip(1).map(String::as_str);
+    while let Some(arg) = rest.next() {
+        if arg == "--" {
+            words.extend(rest.by_ref());
+            break;
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            words.push(arg);
+            continue;
+        }
+
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .or_else(|| rest.next())
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| format!("{flag} needs a value"))
+        };
+        match flag {
+            "--profile" => options.profile = Some(value()?.to_string()),
+            "--data-dir" => options.data_dir = Some(absolute(Path::new(value()?))?),
+            _ if inline.is_some() => return Err(format!("{flag} does not take a value")),
+            "--minimized" | autostart::START_MINIMIZED_ARG => options.start_minimized = true,
+            "--no-vencord" => options.no_vencord = true,
+            "--safe-mode" => options.safe_mode = true,
+            "--devtools" => options.devtools = true,
+            "-h" | "--help" => help = true,
+            "-V" | "--version" => version = true,
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+
+    let command = match words.as_slice() {
+        _ if help => Command::Help,
+        _ if version => Command::Version,
+        [] => Command::Run,
+        ["help"] => Command::Help,
+        ["paths"] => Command::Paths,
+        ["config", "get"] => Command::ConfigGet(None),
+        ["config", "get", key] => Command::ConfigGet(Some(key.to_string())),
+        ["config", "set", key, value] => Command::ConfigSet(key.to_string(), value.to_string()),
+        ["vencord", "status"] => Command::VencordStatus,
+        ["vencord", "update"] => Command::VencordUpdate,
+        // A link that is not one Ghostcord opens still starts the app.
+        [link] if link.contains(':') => {
+            options.route = deeplink::parse(link);
+            Command::Run
+        }
+        _ => return Err(format!("unknown command \"{}\"", words.join(" "))),
+    };
+    Ok(Cli { options, command })
+}
+
+/// Relative paths are kept relative to where Ghostcord was started, not to
+/// wherever a forwarded launch or the autostart entry runs from.
+fn absolute(path: &Path) -> Result<PathBuf, String> {
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    env::current_dir()
+        .map(|dir| dir.join(path))
+        .map_err(|e| e.to_string())
+}
+
+/// Runs a command that needs no window. Paths resolve the way the app
+/// resolves them, `--data-dir` included.
+pub fn run_headless(command: Command, options: &Options) -> Result<(), String> {
+    match command {
+        Command::Run => Err("not a headless command".to_string()),
+        Command::Help => {
+            print!("{USAGE}");
+            Ok(())
+        }
+        Command::Version => {
+            println!("ghostcord-lite {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        Command::Paths => print_paths(options),
+        Command::ConfigGet(key) => config_get(options, key.as_deref()),
+        Command::ConfigSet(key, value) => config_set(options, &key, &value),
+        Command::VencordStatus => vencord_status(options),
+        Command::VencordUpdate => vencord_update(options),
+    }
+}
+
+/// Where the log plugin writes: the app log dir Tauri resolves.
+fn log_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|home| home.join("Library/Logs").join(APP_IDENTIFIER))
+    } else {
+        dirs::data_local_dir().map(|dir| dir.join(APP_IDENTIFIER).join("logs"))
+    }
+}
+
+fn print_paths(options: &Options) -> Result<(), String> {
+    let dir = settings::headless_data_dir()?;
+    let mut paths = vec![
+        ("data", dir.clone()),
+        (
+            "settings",
+            settings::headless_settings_path(&dir, options.profile.as_deref())?,
+        ),
+        ("state", dir.join("state.json")),
+        ("themes", dir.join("themes")),
+        ("vencord", dir.join("vencord")),
+    ];
+    if settings::data_dir_override().is_some() {
+        paths.push(("webview", dir.join("webview")));
+    }
+    if let Some(logs) = log_dir() {
+        paths.push(("logs", logs));
+    }
+    for (name, path) in paths {
+        println!("{name:<10}{}", path.display());
+    }
+    Ok(())
+}
+
+/// Follows a dotted key such as `vencord_source.kind` or `themes.0.file`.
+fn lookup_mut<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    key.split('.').try_fold(value, |value, part| match value {
+        Value::Object(map) => map.get_mut(part),
+        Value::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+        _ => None,
+    })
+}
+
+/// Reads `raw` as JSON, falling back to plain text. A setting that holds a
+/// string keeps `raw` as text unless it is quoted, so `1.2` or `true` do not
+/// turn into a number or a flag there.
+fn parse_value(current: &Value, raw: &str) -> Value {
+    match (current, serde_json::from_str::<Value>(raw)) {
+        (_, Ok(Value::String(text))) => Value::String(text),
+        (Value::String(_), _) | (_, Err(_)) => Value::String(raw.to_string()),
+        (_, Ok(value)) => value,
+    }
+}
+
+fn read_config(options: &Options) -> Result<(PathBuf, AppConfig), String> {
+    let dir = settings::headless_data_dir()?;
+    let path = settings::headless_settings_path(&dir, options.profile.as_deref())?;
+    let cfg = settings::read_settings_file(&path)?;
+    Ok((path, cfg))
+}
+
+fn config_get(options: &Options, key: Option<&str>) -> Result<(), String> {
+    let (_, cfg) = read_config(options)?;
+    let mut value = serde_json::to_value(&cfg).map_err(|e| e.to_string())?;
+    let value = match key {
+        Some(key) => lookup_mut(&mut value, key).ok_or_else(|| format!("unknown setting {key}"))?,
+        None => &mut value,
+    };
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Sets `key` to `raw`, read as JSON when it parses and as a string
+/// otherwise. The result goes through the same checks as the settings panel.
+fn config_set(options: &Options, key: &str, raw: &str) -> Result<(), String> {
+    let (path, mut cfg) = read_config(options)?;
+    if key == migrate::SCHEMA_VERSION_KEY {
+        return Err(format!("{key} is managed by Ghostcord"));
+    }
+
+    // Never serialized, so there is no slot to edit.
+    if key == "vencord_source.auth_token" {
+        cfg.vencord_source.auth_token = Some(raw.to_string());
+        settings::write_settings_file(&path, &cfg)?;
+        println!("Set {key} for {}", path.display());
+        return Ok(());
+    }
+
+    let mut value = cfg.to_versioned()?;
+    let slot = lookup_mut(&mut value, key).ok_or_else(|| format!("unknown setting {key}"))?;
+    *slot = parse_value(slot, raw);
+
+    let loaded = AppConfig::from_versioned(value);
+    if let Some(warning) = loaded.warnings.first() {
+        return Err(format!("{}: {}", warning.field, warning.message));
+    }
+    settings::write_settings_file(&path, &loaded.config)?;
+    println!("Set {key} in {}", path.display());
+    Ok(())
+}
+
+fn vencord_status(options: &Options) -> Result<(), String> {
+    let (_, cfg) = read_config(options)?;
+    let dir = settings::headless_data_dir()?;
+    let versions = vencord::installed_versions_in(&dir, cfg.vencord_pin.as_deref())?;
+
+    println!(
+        "Vencord is {}",
+        if cfg.enable_vencord {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    if let Some(pin) = &cfg.vencord_pin {
+        println!("Pinned to {pin}");
+    }
+    if versions.is_empty() {
+        println!("No versions installed");
+    }
+    for version in versions {
+        println!(
+            "{} {} ({}){}",
+            if version.active { "*" } else { " " },
+            version.tag,
+            version.id,
+            if version.pinned { " pinned" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+/// Progress goes to stderr, on one line per download.
+fn print_progress(progress: VencordProgress) {
+    match progress {
+        VencordProgress::Checking => eprintln!("Checking for Vencord releases…"),
+        VencordProgress::Downloading {
+            name,
+            bytes,
+            total_bytes,
+            ..
+        } => {
+            let kib = |bytes: u64| bytes.div_ceil(1024);
+            match total_bytes {
+                Some(total) => eprint!("\r{name}: {} / {} KiB", kib(bytes), kib(total)),
+                None => eprint!("\r{name}: {} KiB", kib(bytes)),
+            }
+            if total_bytes == Some(bytes) {
+                eprintln!();
+            }
+            let _ = std::io::stderr().flush();
+        }
+        VencordProgress::Installed { tag } => eprintln!("Installed Vencord {tag}"),
+    }
+}
+
+fn vencord_update(options: &Options) -> Result<(), String> {
+    let (_, cfg) = read_config(options)?;
+    let dir = settings::headless_data_dir()?;
+    let assets =
+        tauri::async_runtime::block_on(vencord::sync_assets_in(&dir, &cfg, &print_progress))?;
+    if assets.cached {
+        println!("Vencord {} is up to date", assets.version);
+    } else {
+        println!("Vencord {} is ready", assets.version);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_launch_flags() {
+        let cli = parse(&args(&[
+            "ghostcord-lite",
+            "--profile",
+            "work",
+            "--minimized",
+            "--no-vencord",
+            "--devtools",
+            "discord://-/channels/@me",
+        ]))
+        .unwrap();
+        assert_eq!(cli.command, Command::Run);
+        assert_eq!(
+            cli.options,
+            Options {
+                route: Some("/channels/@me".to_string()),
+                profile: Some("work".to_string()),
+                start_minimized: true,
+                no_vencord: true,
+                devtools: true,
+                ..Options::default()
+            }
+        );
+
+        let cli = parse(&args(&[
+            "ghostcord-lite",
+            "--profile=home",
+            "--start-minimized",
+            "--safe-mode",
+            "--data-dir=/tmp/gc",
+        ]))
+        .unwrap();
+        assert_eq!(cli.options.profile.as_deref(), Some("home"));
+        assert!(cli.options.start_minimized && cli.options.safe_mode);
+        assert_eq!(cli.options.data_dir, Some(PathBuf::from("/tmp/gc")));
+
+        // The program name is never taken for a link, and links Ghostcord
+        // does not open still start it.
+        let cli = parse(&args(&["https://discord.com/app"])).unwrap();
+        assert_eq!(
+            (cli.command, cli.options),
+            (Command::Run, Options::default())
+        );
+        let cli = parse(&args(&["ghostcord-lite", "https://example.com/"])).unwrap();
+        assert_eq!((cli.command, cli.options.route), (Command::Run, None));
+    }
+
+    #[test]
+    fn parses_headless_commands() {
+        let parse_command = |list: &[&str]| parse(&args(list)).map(|cli| cli.command);
+        assert_eq!(parse_command(&["gc", "paths"]), Ok(Command::Paths));
+        assert_eq!(
+            parse_command(&["gc", "--profile", "work", "config", "get", "themes.0.file"]),
+            Ok(Command::ConfigGet(Some("themes.0.file".to_string())))
+        );
+        assert_eq!(
+            parse_command(&["gc", "config", "set", "--", "theme_max_bytes", "-1"]),
+            Ok(Command::ConfigSet(
+                "theme_max_bytes".to_string(),
+                "-1".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_command(&["gc", "vencord", "update"]),
+            Ok(Command::VencordUpdate)
+        );
+        assert_eq!(parse_command(&["gc", "paths", "--help"]), Ok(Command::Help));
+
+        assert!(parse_command(&["gc", "config"]).is_err());
+        assert!(parse_command(&["gc", "--profile"]).is_err());
+        assert!(parse_command(&["gc", "--devtools=yes"]).is_err());
+        assert!(parse_command(&["gc", "--frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn dotted_keys_reach_nested_settings() {
+        let mut value = serde_json::json!({
+            "vencord_source": { "kind": "github" },
+            "themes": [{ "file": "a.css" }],
+        });
+        assert_eq!(
+            lookup_mut(&mut value, "vencord_source.kind").cloned(),
+            Some(Value::from("github"))
+        );
+        assert_eq!(
+            lookup_mut(&mut value, "themes.0.file").cloned(),
+            Some(Value::from("a.css"))
+        );
+        assert!(lookup_mut(&mut value, "themes.1").is_none());
+        assert!(lookup_mut(&mut value, "nope").is_none());
+    }
+
+    #[test]
+    fn string_settings_keep_raw_values_as_text() {
+        let text = Value::from("v1.2.0");
+        assert_eq!(parse_value(&text, "1.2"), Value::from("1.2"));
+        assert_eq!(parse_value(&text, "true"), Value::from("true"));
+        assert_eq!(parse_value(&text, "\"quoted\""), Value::from("quoted"));
+
+        assert_eq!(parse_value(&Value::Bool(false), "true"), Value::Bool(true));
+        assert_eq!(parse_value(&Value::from(1024), "2048"), Value::from(2048));
+        assert_eq!(parse_value(&Value::Null, "v1.2.0"), Value::from("v1.2.0"));
+        assert_eq!(
+            parse_value(&Value::Null, "[\"a\"]"),
+            serde_json::json!(["a"])
+        );
+    }
+}
@@ -1,9 +1,15 @@
-use std::fs;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use tauri::{AppHandle, Manager, State};
 use tauri_plugin_dialog::{DialogExt, FilePath};
 
-use crate::{config::AppConfig, settings, state, vencord};
+use crate::{
+    config::{AppConfig, FieldError, LoadWarning},
+    bundle, protocols, safe_mode, settings, splash, state, themes, vencord,
+};
 
 #[tauri::command]
 pub fn load_config(
@@ -30,17 +36,147 @@ pub fn get_settings(
     Ok(store.get())
 }
 
+#[tauri::command]
+pub fn get_settings_warnings(
+    store: State<settings::SettingsStore>,
+) -> Vec<LoadWarning> {
+    store.warnings()
+}
+
+#[tauri::command]
+pub fn validate_settings(cfg: AppConfig) -> Vec<FieldError> {
+    cfg.validate()
+}
+
 #[tauri::command]
 pub fn set_settings(
     app: AppHandle,
     store: State<settings::SettingsStore>,
     cfg: AppConfig,
 ) -> Result<AppConfig, String> {
-    let cfg = settings::save_settings(&app, &store, cfg)?;
-    let _ = apply_config_to_main(app, cfg.clone());
+    settings::save_settings(&app, &store, cfg)
+}
+
+#[tauri::command]
+pub fn get_safe_mode(app: AppHandle) -> safe_mode::SafeModeStatus {
+    safe_mode::status(&app)
+}
+
+/// Turns settings skipped by safe mode back on: `key`, or all of them.
+#[tauri::command]
+pub fn leave_safe_mode(
+    app: AppHandle,
+    key: Option<String>,
+) -> Result<safe_mode::SafeModeStatus, String> {
+    safe_mode::reenable(&app, key.as_deref())
+}
+
+/// Dismisses the note about the Vencord pin a rollback set, lifting the
+/// pin when `unpin` is set.
+#[tauri::command]
+pub fn release_recovery_pin(
+    app: AppHandle,
+    unpin: bool,
+) -> Result<safe_mode::SafeModeStatus, String> {
+    safe_mode::release_recovery_pin(&app, unpin)
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<settings::ProfileInfo>, String> {
+    settings::list_profiles(&app)
+}
+
+#[tauri::command]
+pub fn create_profile(app: AppHandle, name: String) -> Result<(), String> {
+    settings::create_profile(&app, &name, None)
+}
+
+#[tauri::command]
+pub fn clone_profile(app: AppHandle, from: String, name: String) -> Result<(), String> {
+    settings::create_profile(&app, &name, Some(&from))
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    settings::delete_profile(&app, &name)
+}
+
+/// Loads another profile; settings listeners apply what changed to the main
+/// window. Vencord cannot be unloaded from a running page, so turning it
+/// off reloads Discord instead.
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    store: State<settings::SettingsStore>,
+    name: String,
+) -> Result<AppConfig, String> {
+    let vencord_was_enabled = store.get().enable_vencord;
+    let cfg = settings::switch_profile(&app, &store, &name)?;
+
+    if vencord_was_enabled && !cfg.enable_vencord {
+        let window = app
+            .get_webview_window("main")
+            .ok_or("main window not found")?;
+        window.eval("window.location.reload()").map_err(|e| e.to_string())?;
+    }
     Ok(cfg)
 }
 
+#[tauri::command]
+pub fn export_bundle(app: AppHandle, include_state: bool) -> Result<Option<String>, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.dialog()
+        .file()
+        .add_filter("Ghostcord bundle", &["json"])
+        .set_file_name("ghostcord-bundle.json")
+        .save_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    match rx.recv().ok().flatten().map(file_path_string) {
+        Some(path) => {
+            bundle::export(&app, Path::new(&path), include_state)?;
+            Ok(Some(path))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Asks for a bundle and previews importing it. The file is remembered for
+/// `import_bundle`, so the page never hands over a path of its own.
+#[tauri::command]
+pub fn preview_bundle_import(
+    app: AppHandle,
+    pending: State<bundle::PendingImport>,
+) -> Result<Option<bundle::BundlePreview>, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.dialog()
+        .file()
+        .add_filter("Ghostcord bundle", &["json"])
+        .pick_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    let Some(path) = rx.recv().ok().flatten().map(file_path_string) else {
+        return Ok(None);
+    };
+    let path = PathBuf::from(path);
+    let preview = bundle::preview(&app, &path)?;
+    pending.set(path);
+    Ok(Some(preview))
+}
+
+/// Imports the bundle last previewed.
+#[tauri::command]
+pub fn import_bundle(
+    app: AppHandle,
+    pending: State<bundle::PendingImport>,
+    include_state: bool,
+) -> Result<AppConfig, String> {
+    let path = pending.take().ok_or("preview a bundle before importing it")?;
+    bundle::import(&app, &path, include_state)
+}
+
 #[tauri::command]
 pub fn load_state(
     app: AppHandle,
@@ -65,11 +201,50 @@ pub async fn ensure_vencord_assets(
     vencord::ensure_vencord_assets(&app).await
 }
 
+#[tauri::command]
+pub async fn check_vencord_update(
+    app: AppHandle,
+) -> Result<Option<vencord::VencordUpdate>, String> {
+    vencord::check_for_update(&app).await
+}
+
+#[tauri::command]
+pub async fn update_vencord(app: AppHandle) -> Result<vencord::VencordAssets, String> {
+    vencord::sync_vencord_assets(&app).await
+}
+
+#[tauri::command]
+pub fn cancel_vencord_download() {
+    vencord::cancel_downloads();
+}
+
+#[tauri::command]
+pub fn list_vencord_versions(app: AppHandle) -> Result<Vec<vencord::VencordVersion>, String> {
+    vencord::list_installed_versions(&app)
+}
+
+#[tauri::command]
+pub fn rollback_vencord(
+    app: AppHandle,
+    version: Option<String>,
+) -> Result<vencord::VencordAssets, String> {
+    vencord::rollback(&app, version)
+}
+
 #[tauri::command]
 pub async fn apply_vencord_to_main(
     app: AppHandle,
 ) -> Result<(), String> {
-    let assets = vencord::ensure_vencord_assets(&app).await?;
+    if app
+        .state::<settings::SettingsStore>()
+        .is_off_for_session("enable_vencord")
+    {
+        splash::assets_ready(&app);
+        return Err("Vencord is turned off for this session".to_string());
+    }
+    let assets = vencord::ensure_vencord_assets(&app).await;
+    splash::assets_ready(&app);
+    let assets = assets?;
     let preload = fs::read_to_string(&assets.preload_path).map_err(|e| e.to_string())?;
     let main = fs::read_to_string(&assets.main_path).map_err(|e| e.to_string())?;
 
@@ -101,8 +276,10 @@ pub async fn apply_vencord_to_main(
     Ok(())
 }
 
+/// Lets the user pick a theme and adds it to the active profile. Returns the
+/// updated settings, or `None` if the dialog was cancelled.
 #[tauri::command]
-pub fn pick_theme_file(app: AppHandle) -> Option<String> {
+pub fn pick_theme_file(app: AppHandle) -> Result<Option<AppConfig>, String> {
     let (tx, rx) = std::sync::mpsc::channel();
 
     app.dialog()
@@ -112,30 +289,96 @@ pub fn pick_theme_file(app: AppHandle) -> Option<String> {
             let _ = tx.send(file_path);
         });
 
-    rx.recv()
-        .ok()
-        .flatten()
-        .and_then(|p| match p {
-            FilePath::Path(path) => Some(path.to_string_lossy().to_string()),
-            FilePath::Url(url) => Some(url.to_string()),
-        })
+    match rx.recv().ok().flatten().map(file_path_string) {
+        Some(path) => themes::install(&app, Path::new(&path)).map(Some),
+        None => Ok(None),
+    }
 }
 
 #[tauri::command]
-pub fn apply_config_to_main(app: AppHandle, cfg: AppConfig) -> Result<(), String> {
-    let mut cfg = cfg.sanitize();
-    if cfg.theme_css.is_none() {
-        if let Some(path) = cfg.theme_path.as_deref() {
-            if let Ok(contents) = fs::read_to_string(path) {
-                cfg.theme_css = Some(contents);
-            }
-        }
+pub fn list_themes(app: AppHandle) -> Result<Vec<themes::ThemeInfo>, String> {
+    themes::list(&app)
+}
+
+/// Lists a file from the theme library; files elsewhere come in through
+/// `pick_theme_file`.
+#[tauri::command]
+pub fn install_theme(app: AppHandle, file: String) -> Result<AppConfig, String> {
+    let name = themes::safe_file_name(&file)
+        .filter(|name| *name == file)
+        .ok_or_else(|| format!("{file} is not a file in the theme library"))?;
+    themes::install(&app, &themes::themes_dir(&app)?.join(name))
+}
+
+#[tauri::command]
+pub async fn install_theme_url(app: AppHandle, url: String) -> Result<AppConfig, String> {
+    themes::install_url(&app, &url).await
+}
+
+#[tauri::command]
+pub async fn refresh_themes(app: AppHandle) -> Result<bool, String> {
+    let changed = themes::refresh_remote(&app).await?;
+    if changed {
+        let cfg = app.state::<settings::SettingsStore>().get();
+        apply_config_to_main(app, cfg)?;
+    }
+    Ok(changed)
+}
+
+/// Where files for `ghostcord://localhost/...` URLs go.
+#[tauri::command]
+pub fn get_assets_dir(app: AppHandle) -> Result<String, String> {
+    protocols::assets_dir(&app).map(|dir| dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn read_custom_css(app: AppHandle) -> Result<String, String> {
+    themes::read_custom_css(&app)
+}
+
+#[tauri::command]
+pub fn save_custom_css(app: AppHandle, css: String) -> Result<AppConfig, String> {
+    themes::save_custom_css(&app, &css)
+}
+
+#[tauri::command]
+pub fn remove_theme(app: AppHandle, file: String) -> Result<AppConfig, String> {
+    themes::remove(&app, &file)
+}
+
+#[tauri::command]
+pub fn set_theme_enabled(app: AppHandle, file: String, enabled: bool) -> Result<AppConfig, String> {
+    themes::set_enabled(&app, &file, enabled)
+}
+
+#[tauri::command]
+pub fn reorder_themes(app: AppHandle, files: Vec<String>) -> Result<AppConfig, String> {
+    themes::reorder(&app, &files)
+}
+
+fn file_path_string(file_path: FilePath) -> String {
+    match file_path {
+        FilePath::Path(path) => path.to_string_lossy().to_string(),
+        FilePath::Url(url) => url.to_string(),
     }
+}
+
+#[tauri::command]
+pub fn apply_config_to_main(app: AppHandle, cfg: AppConfig) -> Result<(), String> {
+    let theme_styles = if cfg.enable_theme {
+        themes::enabled_styles(&app, &cfg)
+    } else {
+        Vec::new()
+    };
     let window = app
         .get_webview_window("main")
         .ok_or("main window not found")?;
-    let payload = serde_json::to_string(&cfg)
-        .map_err(|e| e.to_string())?;
+    let mut payload = serde_json::to_value(&cfg).map_err(|e| e.to_string())?;
+    if let Some(object) = payload.as_object_mut() {
+        let styles = serde_json::to_value(theme_styles).map_err(|e| e.to_string())?;
+        object.insert("theme_styles".to_string(), styles);
+    }
+    let payload = payload.to_string();
     let script = format!(
         r#"
 (() => {{
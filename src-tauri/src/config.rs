@@ -1,29 +1,741 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::migrate::{self, Migration};
+
+/// Upgrade steps for `settings.json`; the index is the version migrated from.
+pub const MIGRATIONS: &[Migration] = &[
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VencordSourceKind {
+    /// GitHub releases API or a compatible mirror of it.
+    #[default]
+    Github,
+    /// Directory with `latest.json` and `tags/<tag>.json` release files.
+    Static,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct VencordSource {
+    pub kind: VencordSourceKind,
+    /// Releases API base or static directory; `file://` works for static.
+    /// Falls back to the upstream GitHub API when unset.
+    pub base_url: Option<String>,
+    /// Never serialized, so it stays out of the settings file and of every
+    /// payload sent to the page; it lives in the profile's secrets file.
+    #[serde(skip_serializing)]
+    pub auth_token: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// An installed theme. `file` is a name inside the themes directory; older
+/// configs may hold an absolute path, which is used where it is. Themes
+/// with a `url` are downloaded and cached, and `file` only names them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeEntry {
+    pub file: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Missing fields take their value from `AppConfig::default()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
-    pub theme_path: Option<String>,
-    pub theme_css: Option<String>,
+    /// Installed themes in injection order; later ones win.
+    pub themes: Vec<ThemeEntry>,
+    /// Largest theme, with its imports inlined, that will be injected.
+    pub theme_max_bytes: u64,
+    /// Hosts themes may load remote `@import`s and `url()`s from. A leading
+    /// `*.` also matches subdomains. Anything else is stripped.
+    pub theme_allowed_hosts: Vec<String>,
     pub enable_theme: bool,
     pub enable_blockers: bool,
     pub enable_perf_css: bool,
     pub enable_vencord: bool,
+    /// Re-inject the theme when its file or a local `@import` changes.
+    pub theme_hot_reload: bool,
+    /// Closing the window hides it to the tray instead of quitting.
+    pub close_to_tray: bool,
+    /// Start hidden in the tray (or minimized when there is no tray).
+    pub start_minimized: bool,
+    /// Launch on login through an XDG autostart entry.
+    pub autostart: bool,
+    /// Pass `--start-minimized` from the autostart entry.
+    pub autostart_minimized: bool,
+    /// Register as the `discord://` link handler (Linux).
+    pub handle_discord_links: bool,
+    /// Vencord release tag or installed version id to stay on instead of
+    /// following the latest release.
+    pub vencord_pin: Option<String>,
+    /// Start from the cached Vencord install and check for releases in the
+    /// background instead of before injection.
+    pub vencord_offline_first: bool,
+    pub vencord_update_interval_minutes: Option<u64>,
+    /// How long a Vencord request may wait for the server, both for a
+    /// response and between chunks of a download.
+    pub vencord_timeout_seconds: Option<u64>,
+    /// Extra attempts after a failed Vencord request, with growing delays.
+    pub vencord_download_retries: Option<u32>,
+    pub vencord_source: VencordSource,
+    /// Keys this build does not know about, kept so that saving after a
+    /// downgrade does not wipe settings written by a newer version.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for AppConfig {
+    // sensible defaults for 8GB machines
+    fn default() -> Self {
+        Self {
+            themes: Vec::new(),
+            theme_max_bytes: 1024 * 1024,
+            theme_allowed_hosts: [
+                "fonts.googleapis.com",
+                "fonts.gstatic.com",
+                "cdn.jsdelivr.net",
+                "raw.githubusercontent.com",
+                "*.github.io",
+                "cdn.discordapp.com",
+                "media.discordapp.net",
+            ]
+            .map(String::from)
+            .to_vec(),
+            enable_theme: false,
+            enable_blockers: true,
+            enable_perf_css: true,
+            enable_vencord: false,
+            theme_hot_reload: false,
+            close_to_tray: false,
+            start_minimized: false,
+            autostart: false,
+            autostart_minimized: true,
+            handle_discord_links: false,
+            vencord_pin: None,
+            vencord_offline_first: false,
+            vencord_update_interval_minutes: None,
+            vencord_timeout_seconds: None,
+            vencord_download_retries: None,
+            vencord_source: VencordSource::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// A settings value that fails validation, reported per field so the
+/// settings panel can point at it.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A settings field that could not be loaded and fell back to its default.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadWarning {
+    pub field: String,
+    pub message: String,
+    pub value: Value,
+}
+
+pub struct LoadedConfig {
+    pub config: AppConfig,
+    pub migrated: bool,
+    pub warnings: Vec<LoadWarning>,
+}
+
+impl From<AppConfig> for LoadedConfig {
+    fn from(config: AppConfig) -> Self {
+        Self {
+            config,
+            migrated: false,
+            warnings: Vec::new(),
+        }
+    }
 }
 
 impl AppConfig {
-    pub fn sanitize(mut self) -> Self {
-        // sensible defaults for 8GB machines
-        if self.enable_blockers == false
-            && self.enable_perf_css == false
-            && self.enable_vencord == false
-            && self.enable_theme == false
-            && self.theme_path.is_none()
-            && self.theme_css.is_none()
+    /// The switch for one of the things injected into Discord, by settings
+    /// key.
+    pub fn injection_mut(&mut self, key: &str) -> Option<&mut bool> {
+        match key {
+            "enable_theme" => Some(&mut self.enable_theme),
+            "enable_blockers" => Some(&mut self.enable_blockers),
+            "enable_perf_css" => Some(&mut self.enable_perf_css),
+            "enable_vencord" => Some(&mut self.enable_vencord),
+            _ => None,
+        }
+    }
+
+    /// Checks values that deserialize fine but cannot work, without
+    /// changing anything.
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        for (index, theme) in self.themes.iter().enumerate() {
+            let field = format!("themes.{index}");
+            let path = Path::new(&theme.file);
+            if let Some(url) = theme.url.as_deref() {
+                match reqwest::Url::parse(url) {
+                    Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+                    Ok(parsed) => errors.push(FieldError::new(
+                        &field,
+                        format!("unsupported scheme {}", parsed.scheme()),
+                    )),
+                    Err(err) => errors.push(FieldError::new(&field, format!("{url}: {err}"))),
+                }
+            } else if path.is_absolute() {
+                if !path.is_file() {
+                    errors.push(FieldError::new(&field, format!("{} does not exist", theme.file)));
+                }
+            } else if path.file_name().map(|name| name.to_string_lossy()).as_deref()
+                != Some(theme.file.as_str())
+            {
+                errors.push(FieldError::new(&field, format!("invalid theme file {}", theme.file)));
+            }
+            if self.themes[..index].iter().any(|other| other.file == theme.file) {
+                errors.push(FieldError::new(&field, format!("{} is listed twice", theme.file)));
+            }
+        }
+
+        if self.theme_max_bytes < 1024 {
+            errors.push(FieldError::new("theme_max_bytes", "must be at least 1 KiB"));
+        }
+        for (index, host) in self.theme_allowed_hosts.iter().enumerate() {
+            let bare = host.strip_prefix("*.").unwrap_or(host);
+            let valid = !bare.is_empty()
+                && bare
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+            if !valid {
+                errors.push(FieldError::new(
+                    &format!("theme_allowed_hosts.{index}"),
+                    format!("{host} is not a host name"),
+                ));
+            }
+        }
+
+        if self.vencord_timeout_seconds == Some(0) {
+            errors.push(FieldError::new(
+                "vencord_timeout_seconds",
+                "must be at least 1 second",
+            ));
+        }
+        if self.vencord_download_retries.is_some_and(|retries| retries > 10) {
+            errors.push(FieldError::new(
+                "vencord_download_retries",
+                "must be at most 10",
+            ));
+        }
+
+        if self.vencord_update_interval_minutes == Some(0) {
+            errors.push(FieldError::new(
+                "vencord_update_interval_minutes",
+                "must be at least 1 minute",
+            ));
+        }
+
+        match self.vencord_source.base_url.as_deref() {
+            Some(url) => match reqwest::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+                Ok(parsed)
+                    if parsed.scheme() == "file"
+                        && self.vencord_source.kind == VencordSourceKind::Static => {}
+                Ok(parsed) => errors.push(FieldError::new(
+                    "vencord_source.base_url",
+                    format!("unsupported scheme {}", parsed.scheme()),
+                )),
+                Err(err) => errors.push(FieldError::new(
+                    "vencord_source.base_url",
+                    err.to_string(),
+                )),
+            },
+            None if self.vencord_source.kind == VencordSourceKind::Static => {
+                errors.push(FieldError::new(
+                    "vencord_source.base_url",
+                    "a static source needs a base URL",
+                ));
+            }
+            None => {}
+        }
+
+        errors
+    }
+
+    /// Migrates a raw settings document to the latest schema and
+    /// deserializes it field by field: a field with an unusable value falls
+    /// back to its default and is reported as a warning instead of failing
+    /// the whole load.
+    pub fn from_versioned(value: Value) -> LoadedConfig {
+        Self::from_versioned_with(value, MIGRATIONS)
+    }
+
+    /// A migration that fails leaves `schema_version` at the last step that
+    /// worked, so saving keeps it there and the next load tries again.
+    fn from_versioned_with(mut value: Value, migrations: &[Migration]) -> LoadedConfig {
+        let mut warnings = Vec::new();
+        let (migrated, failed) = match migrate::run(&mut value, migrations) {
+            Ok(migrated) => (migrated, false),
+            Err(err) => {
+                warnings.push(LoadWarning {
+                    field: migrate::SCHEMA_VERSION_KEY.to_string(),
+                    message: err,
+                    value: value.get(migrate::SCHEMA_VERSION_KEY).cloned().unwrap_or_default(),
+                });
+                (false, true)
+            }
+        };
+
+        let Value::Object(mut fields) = value else {
+            warnings.push(LoadWarning {
+                field: String::new(),
+                message: "settings are not a JSON object; using defaults".to_string(),
+                value,
+            });
+            return LoadedConfig {
+                config: Self::default(),
+                migrated,
+                warnings,
+            };
+        };
+        if !failed {
+            migrate::strip_version(&mut fields, migrations);
+        }
+
+        let defaults = match serde_json::to_value(Self::default()) {
+            Ok(Value::Object(defaults)) => defaults,
+            _ => Map::new(),
+        };
+        let mut merged = defaults.clone();
+        for (key, field) in fields {
+            if !defaults.contains_key(&key) {
+                merged.insert(key, field);
+                continue;
+            }
+            let mut probe = defaults.clone();
+            probe.insert(key.clone(), field.clone());
+            match serde_json::from_value::<Self>(Value::Object(probe)) {
+                Ok(_) => {
+                    merged.insert(key, field);
+                }
+                Err(err) => {
+                    log::warn!("Settings field {key} is invalid, using default: {err}");
+                    warnings.push(LoadWarning {
+                        field: key,
+                        message: err.to_string(),
+                        value: field,
+                    });
+                }
+            }
+        }
+
+        let config = serde_json::from_value::<Self>(Value::Object(merged)).unwrap_or_default();
+        LoadedConfig {
+            config,
+            migrated,
+            warnings,
+        }
+    }
+
+    pub fn to_versioned(&self) -> Result<Value, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        migrate::stamp(&mut value, MIGRATIONS);
+        Ok(value)
+    }
+}
+
+fn coerce_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(flag) => Some(*flag),
+        Value::Number(number) => number.as_i64().map(|n| n != 0),
+        Value::String(text) => match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Some(true),
+            "false" | "0" | "no" | "off" | "" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// v0 covers unversioned `settings.json` and the legacy `config.json`:
+/// toggles may be missing or stored as strings/numbers, and the old UI
+/// saved empty theme fields as `""`.
+fn migrate_v0_to_v1(cfg: &mut Map<String, Value>) -> Result<(), String> {
+    for key in [
+        "enable_theme",
+        "enable_blockers",
+        "enable_perf_css",
+        "enable_vencord",
+    ] {
+        // Values that cannot be coerced are left for field-level defaulting.
+        let flag = match cfg.get(key) {
+            None | Some(Value::Null) => Some(false),
+            Some(value) => coerce_bool(value),
+        };
+        if let Some(flag) = flag {
+            cfg.insert(key.to_string(), Value::Bool(flag));
+        }
+    }
+
+    for key in ["theme_path", "theme_css"] {
+        if cfg
+            .get(key)
+            .and_then(Value::as_str)
+            .is_some_and(|text| text.trim().is_empty())
         {
-            self.enable_blockers = true;
-            self.enable_perf_css = true;
-            self.enable_vencord = false;
+            cfg.insert(key.to_string(), Value::Null);
         }
-        self
+    }
+    Ok(())
+}
+
+/// Settings used to be "sanitized" on every load: with every toggle off and
+/// no theme, blockers and perf CSS were switched back on. Apply that once so
+/// those users keep what they saw, after which all-off is a valid choice.
+fn migrate_v1_to_v2(cfg: &mut Map<String, Value>) -> Result<(), String> {
+    let all_off = [
+        "enable_theme",
+        "enable_blockers",
+        "enable_perf_css",
+        "enable_vencord",
+    ]
+    .iter()
+    .all(|key| !cfg.get(*key).and_then(Value::as_bool).unwrap_or(false));
+    let no_theme = ["theme_path", "theme_css"]
+        .iter()
+        .all(|key| cfg.get(*key).map_or(true, Value::is_null));
+
+    if all_off && no_theme {
+        cfg.insert("enable_blockers".to_string(), Value::Bool(true));
+        cfg.insert("enable_perf_css".to_string(), Value::Bool(true));
+    }
+    Ok(())
+}
+
+/// The single `theme_path` slot became the `themes` list. The old file stays
+/// where it is rather than being copied into the themes directory.
+fn migrate_v2_to_v3(cfg: &mut Map<String, Value>) -> Result<(), String> {
+    let theme_path = cfg.remove("theme_path");
+    if cfg.get("themes").is_some_and(Value::is_array) {
+        return Ok(());
+    }
+    let themes = match theme_path {
+        Some(Value::String(path)) if !path.trim().is_empty() => {
+            vec![serde_json::json!({ "file": path, "enabled": true })]
+        }
+        _ => Vec::new(),
+    };
+    cfg.insert("themes".to_string(), Value::Array(themes));
+    Ok(())
+}
+
+/// Inline custom CSS from before themes were files, kept until loading the
+/// settings moves it into the themes library.
+pub const PENDING_THEME_CSS_KEY: &str = "pending_theme_css";
+
+/// Custom CSS used to be kept inline as `theme_css`. Writing it out needs
+/// the data dir in use, so this only sets it aside under
+/// [`PENDING_THEME_CSS_KEY`] for `themes::adopt_pending_css`.
+fn migrate_v3_to_v4(cfg: &mut Map<String, Value>) -> Result<(), String> {
+    match cfg.remove("theme_css") {
+        Some(Value::String(css)) if !css.trim().is_empty() => {
+            cfg.insert(PENDING_THEME_CSS_KEY.to_string(), Value::String(css));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn failed_migrations_are_tried_again() {
+        fn fails(_: &mut Map<String, Value>) -> Result<(), String> {
+            Err("disk full".to_string())
+        }
+        let migrations: &[Migration] = &[migrate_v0_to_v1, fails];
+
+        let loaded = AppConfig::from_versioned_with(json!({ "enable_theme": true }), migrations);
+        assert!(!loaded.migrated);
+        assert_eq!(loaded.warnings[0].field, migrate::SCHEMA_VERSION_KEY);
+        assert!(loaded.config.enable_theme);
+
+        let mut saved = serde_json::to_value(&loaded.config).unwrap();
+        migrate::stamp(&mut saved, migrations);
+        assert_eq!(saved[migrate::SCHEMA_VERSION_KEY], json!(1));
+    }
+
+    #[test]
+    fn v3_to_v4_sets_inline_css_aside() {
+        let mut cfg = object(json!({ "theme_css": "a {}", "themes": [] }));
+        migrate_v3_to_v4(&mut cfg).unwrap();
+        assert_eq!(
+            cfg,
+            object(json!({ PENDING_THEME_CSS_KEY: "a {}", "themes": [] }))
+        );
+
+        let mut cfg = object(json!({ "theme_css": " " }));
+        migrate_v3_to_v4(&mut cfg).unwrap();
+        assert!(cfg.is_empty());
+    }
+
+    #[test]
+    fn v0_to_v1_fills_missing_toggles() {
+        let mut cfg = object(json!({ "enable_theme": true }));
+        migrate_v0_to_v1(&mut cfg).unwrap();
+        assert_eq!(cfg["enable_theme"], json!(true));
+        assert_eq!(cfg["enable_blockers"], json!(false));
+        assert_eq!(cfg["enable_perf_css"], json!(false));
+        assert_eq!(cfg["enable_vencord"], json!(false));
+    }
+
+    #[test]
+    fn v0_to_v1_coerces_loose_toggle_types() {
+        let mut cfg = object(json!({
+            "enable_theme": "true",
+            "enable_blockers": 1,
+            "enable_perf_css": "off",
+            "enable_vencord": null,
+        }));
+        migrate_v0_to_v1(&mut cfg).unwrap();
+        assert_eq!(cfg["enable_theme"], json!(true));
+        assert_eq!(cfg["enable_blockers"], json!(true));
+        assert_eq!(cfg["enable_perf_css"], json!(false));
+        assert_eq!(cfg["enable_vencord"], json!(false));
+    }
+
+    #[test]
+    fn v0_to_v1_leaves_unrecognised_toggle() {
+        let mut cfg = object(json!({ "enable_theme": "sometimes" }));
+        migrate_v0_to_v1(&mut cfg).unwrap();
+        assert_eq!(cfg["enable_theme"], json!("sometimes"));
+    }
+
+    #[test]
+    fn v0_to_v1_clears_empty_theme_fields() {
+        let mut cfg = object(json!({ "theme_path": "  ", "theme_css": "a{}" }));
+        migrate_v0_to_v1(&mut cfg).unwrap();
+        assert_eq!(cfg["theme_path"], Value::Null);
+        assert_eq!(cfg["theme_css"], json!("a{}"));
+    }
+
+    #[test]
+    fn v1_to_v2_applies_old_all_off_defaults_once() {
+        let mut cfg = object(json!({
+            "enable_theme": false,
+            "enable_blockers": false,
+            "enable_perf_css": false,
+            "enable_vencord": false,
+            "theme_path": null,
+        }));
+        migrate_v1_to_v2(&mut cfg).unwrap();
+        assert_eq!(cfg["enable_blockers"], json!(true));
+        assert_eq!(cfg["enable_perf_css"], json!(true));
+        assert_eq!(cfg["enable_vencord"], json!(false));
+    }
+
+    #[test]
+    fn v1_to_v2_keeps_explicit_choices() {
+        let mut cfg = object(json!({
+            "enable_theme": false,
+            "enable_blockers": false,
+            "enable_perf_css": false,
+            "enable_vencord": true,
+        }));
+        migrate_v1_to_v2(&mut cfg).unwrap();
+        assert_eq!(cfg["enable_blockers"], json!(false));
+        assert_eq!(cfg["enable_perf_css"], json!(false));
+    }
+
+    #[test]
+    fn all_off_is_kept_at_latest_schema() {
+        let cfg = AppConfig {
+            enable_blockers: false,
+            enable_perf_css: false,
+            ..AppConfig::default()
+        };
+        let loaded = AppConfig::from_versioned(cfg.to_versioned().unwrap());
+        assert!(!loaded.config.enable_blockers);
+        assert!(!loaded.config.enable_perf_css);
+    }
+
+    #[test]
+    fn v2_to_v3_moves_theme_path_into_themes() {
+        let mut cfg = object(json!({ "theme_path": "/home/me/dark.theme.css" }));
+        migrate_v2_to_v3(&mut cfg).unwrap();
+        assert!(!cfg.contains_key("theme_path"));
+        assert_eq!(
+            cfg["themes"],
+            json!([{ "file": "/home/me/dark.theme.css", "enabled": true }])
+        );
+
+        let mut cfg = object(json!({ "theme_path": null }));
+        migrate_v2_to_v3(&mut cfg).unwrap();
+        assert_eq!(cfg["themes"], json!([]));
+    }
+
+    #[test]
+    fn validate_reports_bad_theme_entries() {
+        let theme = |file: &str| ThemeEntry {
+            file: file.to_string(),
+            enabled: true,
+            url: None,
+        };
+        let cfg = AppConfig {
+            themes: vec![
+                theme("/nonexistent/ghostcord.theme.css"),
+                theme("../escape.css"),
+                theme("dark.theme.css"),
+                theme("dark.theme.css"),
+                ThemeEntry {
+                    url: Some("ftp://example.com/a.css".to_string()),
+                    ..theme("a.css")
+                },
+            ],
+            ..AppConfig::default()
+        };
+        let fields = cfg
+            .validate()
+            .into_iter()
+            .map(|error| error.field)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, ["themes.0", "themes.1", "themes.3", "themes.4"]);
+    }
+
+    #[test]
+    fn validate_checks_vencord_source() {
+        let mut cfg = AppConfig::default();
+        cfg.vencord_source.kind = VencordSourceKind::Static;
+        assert_eq!(cfg.validate()[0].field, "vencord_source.base_url");
+
+        cfg.vencord_source.base_url = Some("file:///srv/vencord".to_string());
+        assert!(cfg.validate().is_empty());
+
+        cfg.vencord_source.kind = VencordSourceKind::Github;
+        assert_eq!(cfg.validate()[0].field, "vencord_source.base_url");
+    }
+
+    #[test]
+    fn legacy_document_migrates_to_latest() {
+        let legacy = json!({ "theme_path": "", "enable_blockers": "true" });
+        let loaded = AppConfig::from_versioned(legacy);
+        assert!(loaded.migrated);
+        assert!(loaded.warnings.is_empty());
+        assert!(loaded.config.enable_blockers);
+        assert!(loaded.config.themes.is_empty());
+    }
+
+    #[test]
+    fn versioned_round_trip_skips_migrations() {
+        let value = AppConfig::default().to_versioned().unwrap();
+        assert_eq!(
+            migrate::schema_version(&value).unwrap(),
+            MIGRATIONS.len() as u64
+        );
+        assert!(!AppConfig::from_versioned(value).migrated);
+    }
+
+    #[test]
+    fn newer_schema_is_loaded_as_is() {
+        let value = json!({
+            "schema_version": MIGRATIONS.len() as u64 + 1,
+            "enable_theme": false,
+            "enable_blockers": true,
+            "enable_perf_css": false,
+            "enable_vencord": false,
+        });
+        let loaded = AppConfig::from_versioned(value);
+        assert!(!loaded.migrated);
+        assert!(loaded.config.enable_blockers);
+    }
+
+    #[test]
+    fn invalid_field_falls_back_with_warning() {
+        let value = json!({
+            "schema_version": MIGRATIONS.len() as u64,
+            "enable_theme": "sometimes",
+            "enable_blockers": true,
+            "vencord_pin": 42,
+        });
+        let loaded = AppConfig::from_versioned(value);
+        assert!(loaded.config.enable_blockers);
+        assert!(!loaded.config.enable_theme);
+        assert!(loaded.config.vencord_pin.is_none());
+        let mut fields = loaded
+            .warnings
+            .iter()
+            .map(|warning| warning.field.as_str())
+            .collect::<Vec<_>>();
+        fields.sort();
+        assert_eq!(fields, ["enable_theme", "vencord_pin"]);
+    }
+
+    #[test]
+    fn unknown_fields_survive_a_round_trip() {
+        let value = json!({ "enable_blockers": true, "future_toggle": { "a": 1 } });
+        let loaded = AppConfig::from_versioned(value);
+        assert_eq!(loaded.config.extra["future_toggle"], json!({ "a": 1 }));
+        let saved = loaded.config.to_versioned().unwrap();
+        assert_eq!(saved["future_toggle"], json!({ "a": 1 }));
+        assert_eq!(saved["enable_blockers"], json!(true));
+    }
+
+    #[test]
+    fn auth_token_is_read_but_never_written() {
+        let value = json!({
+            "schema_version": MIGRATIONS.len() as u64,
+            "vencord_source": { "base_url": "https://mirror.example", "auth_token": "secret" },
+        });
+        let loaded = AppConfig::from_versioned(value);
+        assert_eq!(loaded.config.vencord_source.auth_token.as_deref(), Some("secret"));
+
+        let saved = loaded.config.to_versioned().unwrap();
+        assert_eq!(saved["vencord_source"]["base_url"], json!("https://mirror.example"));
+        assert!(saved["vencord_source"].get("auth_token").is_none());
+    }
+
+    #[test]
+    fn newer_schema_version_survives_a_save() {
+        let newer = MIGRATIONS.len() as u64 + 1;
+        let value = json!({ "schema_version": newer, "enable_blockers": false });
+        let loaded = AppConfig::from_versioned(value);
+        assert!(!loaded.migrated);
+        assert_eq!(loaded.config.to_versioned().unwrap()["schema_version"], json!(newer));
+
+        let current = AppConfig::default().to_versioned().unwrap();
+        let loaded = AppConfig::from_versioned(current);
+        assert!(!loaded.config.extra.contains_key("schema_version"));
     }
 }
@@ -0,0 +1,236 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tauri::{AppHandle, Manager, Url};
+
+use crate::{autostart, config::AppConfig, persist};
+
+pub const DISCORD_ORIGIN: &str = "https://discord.com";
+const HANDLER_FILE: &str = "ghostcord-lite-url-handler.desktop";
+const SCHEME_MIME: &str = "x-scheme-handler/discord";
+
+/// Hosts whose links open in the app rather than the browser.
+const DISCORD_HOSTS: &[&str] = &[
+    "discord.com",
+    "www.discord.com",
+    "ptb.discord.com",
+    "canary.discord.com",
+    "discordapp.com",
+    "www.discordapp.com",
+];
+
+fn is_snowflake(segment: &str) -> bool {
+    !segment.is_empty() && segment.len() <= 20 && segment.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_invite_code(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment.len() <= 64
+        && segment
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Checks a path against the app routes links may open, returning it in
+/// canonical form. Anything else is refused rather than loaded.
+fn validate_route(segments: &[&str]) -> Option<String> {
+    match segments {
+        [] | ["app"] => Some("/app".to_string()),
+        ["channels", guild, rest @ ..]
+            if (*guild == "@me" || is_snowflake(guild))
+                && rest.len() <= 2
+                && rest.iter().all(|id| is_snowflake(id)) =>
+        {
+            Some(format!("/{}", segments.join("/")))
+        }
+        ["invite", code] if is_invite_code(code) => Some(format!("/invite/{code}")),
+        ["users", id] if is_snowflake(id) => Some(format!("/users/{id}")),
+        _ => None,
+    }
+}
+
+/// Turns a `discord://` link or a Discord web URL into an app route such as
+/// `/channels/@me/123`. Returns `None` for anything that is not one.
+pub fn parse(link: &str) -> Option<String> {
+    let url = Url::parse(link.trim()).ok()?;
+    let mut segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+
+    match url.scheme() {
+        // Discord writes these as `discord://-/channels/...` or
+        // `discord:///channels/...`; some apps drop the placeholder host.
+        "discord" => match url.host_str() {
+            None | Some("") | Some("-") => {}
+            Some(host) => segments.insert(0, host),
+        },
+        "https" | "http" => {
+            let host = url.host_str()?.to_ascii_lowercase();
+            if host == "discord.gg" {
+                let [code] = segments.as_slice() else {
+                    return None;
+                };
+                return is_invite_code(code).then(|| format!("/invite/{code}"));
+            }
+            if !DISCORD_HOSTS.contains(&host.as_str()) {
+                return None;
+            }
+        }
+        _ => return None,
+    }
+    validate_route(&segments)
+}
+
+/// Opens `route` in the main window. Discord's router follows history
+/// changes, so this avoids reloading the client when it is already up.
+pub fn navigate(app: &AppHandle, route: &str) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("main window not found")?;
+    let route_js = serde_json::to_string(route).map_err(|e| e.to_string())?;
+    let origin_js = serde_json::to_string(DISCORD_ORIGIN).map_err(|e| e.to_string())?;
+    let script = format!(
+        r#"
+(() => {{
+  const route = {route_js};
+  if (location.origin === {origin_js}) {{
+    history.pushState(null, '', route);
+    window.dispatchEvent(new PopStateEvent('popstate', {{ state: null }}));
+  }} else {{
+    location.assign({origin_js} + route);
+  }}
+}})();
+"#
+    );
+    window.eval(&script).map_err(|e| e.to_string())?;
+    log::info!("Opened link {route}");
+    Ok(())
+}
+
+/// `$XDG_DATA_HOME/applications`, falling back to `~/.local/share`.
+fn applications_dir() -> Option<PathBuf> {
+    autostart::xdg_dir_from(
+        env::var_os("XDG_DATA_HOME"),
+        env::var_os("HOME"),
+        ".local/share",
+    )
+    .map(|dir| dir.join("applications"))
+}
+
+fn handler_entry(exec: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Ghostcord Lite\n\
+         Exec={} %u\n\
+         Icon=ghostcord-lite\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         MimeType={SCHEME_MIME};\n",
+        autostart::quote_exec_arg(&exec.to_string_lossy())
+    )
+}
+
+fn run_quietly(program: &str, args: &[&str]) {
+    match Command::new(program).args(args).output() {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => log::warn!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) => log::warn!("Could not run {program}: {err}"),
+    }
+}
+
+/// Registers (or unregisters) Ghostcord as the `discord://` handler for
+/// this user, following `handle_discord_links`.
+pub fn sync_protocol_handler(cfg: &AppConfig) -> Result<(), String> {
+    if !cfg!(target_os = "linux") {
+        if cfg.handle_discord_links {
+            return Err("discord:// registration is only supported on Linux".to_string());
+        }
+        return Ok(());
+    }
+    let dir = applications_dir().ok_or("neither XDG_DATA_HOME nor HOME is set")?;
+    let path = dir.join(HANDLER_FILE);
+
+    if !cfg.handle_discord_links {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+            log::info!("Removed discord:// handler {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let entry = handler_entry(&autostart::launch_path()?);
+    if fs::read_to_string(&path).is_ok_and(|current| current == entry) {
+        return Ok(());
+    }
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    persist::write_atomic(&path, entry.as_bytes())?;
+    run_quietly("xdg-mime", &["default", HANDLER_FILE, SCHEME_MIME]);
+    run_quietly("update-desktop-database", &[&dir.to_string_lossy()]);
+    log::info!("Registered discord:// handler {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_discord_scheme_links() {
+        assert_eq!(
+            parse("discord://-/channels/@me/123456789012345678").as_deref(),
+            Some("/channels/@me/123456789012345678")
+        );
+        assert_eq!(
+            parse("discord:///channels/1/2/3").as_deref(),
+            Some("/channels/1/2/3")
+        );
+        assert_eq!(
+            parse("discord://channels/1/2").as_deref(),
+            Some("/channels/1/2")
+        );
+        assert_eq!(parse("discord://-/invite/abc-DEF").as_deref(), Some("/invite/abc-DEF"));
+        assert_eq!(parse("discord://").as_deref(), Some("/app"));
+    }
+
+    #[test]
+    fn parses_web_links() {
+        assert_eq!(
+            parse("https://discord.com/channels/1/2?foo=bar#x").as_deref(),
+            Some("/channels/1/2")
+        );
+        assert_eq!(
+            parse("https://canary.discord.com/users/42").as_deref(),
+            Some("/users/42")
+        );
+        assert_eq!(parse("https://discord.gg/ghostcord").as_deref(), Some("/invite/ghostcord"));
+        assert_eq!(parse("https://DISCORDAPP.com/app").as_deref(), Some("/app"));
+    }
+
+    #[test]
+    fn rejects_other_links() {
+        for link in [
+            "https://evil.example/channels/1/2",
+            "https://discord.com.evil.example/channels/1",
+            "https://discord.com/channels/abc",
+            "https://discord.com/channels/1/2/3/4",
+            "https://discord.com/settings",
+            "discord://-/channels/1/%2e%2e",
+            "discord://-/invite/bad%20code",
+            "https://discord.gg/",
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+            "--start-minimized",
+            "",
+        ] {
+            assert_eq!(parse(link), None, "{link}");
+        }
+    }
+}
@@ -5,7 +5,6 @@
   console.log("%c[Ghostcord] Injected", "color:#7fffd4;font-weight:bold");
 
   const PERF_STYLE_ID = "__ghostcord_perf_css__";
-  const THEME_STYLE_ID = "ghostcord-theme";
   const SETTINGS_PANEL_ID = "__ghostcord_settings_panel__";
   const SETTINGS_ITEM_ID = "__ghostcord_settings_item__";
 
@@ -53,7 +52,7 @@
     if (!window.__GHOSTCORD__) {
       window.__GHOSTCORD__ = {
         injectedAt: performance.now(),
-        perfEnabled: true,
+        perfEnabled: !window.__GHOSTCORD_SAFE_MODE__,
         blockersEnabled: false,
         blockersInstalled: false,
         blockersOriginals: null,
@@ -67,15 +66,21 @@
   }
 
   function applyThemeFromConfig(cfg) {
-    if (!cfg?.enable_theme) {
-      removeStyle(THEME_STYLE_ID);
-      return;
-    }
-    if (cfg.theme_css?.trim()) {
-      injectStyle(THEME_STYLE_ID, cfg.theme_css);
-    } else {
-      removeStyle(THEME_STYLE_ID);
-    }
+    const styles = cfg?.enable_theme ? (cfg.theme_styles || []) : [];
+    const ids = new Set(styles.map((style) => style.id));
+    document.querySelectorAll('style[data-ghostcord-theme]').forEach((el) => {
+      if (!ids.has(el.id)) el.remove();
+    });
+
+    // Re-append in list order so later themes win.
+    styles.forEach((style) => {
+      injectStyle(style.id, style.css);
+      const el = document.getElementById(style.id);
+      if (el) {
+        el.dataset.ghostcordTheme = style.file;
+        document.documentElement.appendChild(el);
+      }
+    });
   }
 
   function applyPerfFromConfig(cfg) {
@@ -357,7 +362,30 @@
           background: rgba(237, 66, 69, 0.3);
           color: #ed4245;
         }
-        #${SETTINGS_PANEL_ID} input[type="text"] {
+        #${SETTINGS_PANEL_ID} .settings-warnings {
+          padding: 10px;
+          margin: 10px 0;
+          border-radius: 3px;
+          background: rgba(250, 166, 26, 0.2);
+          color: #faa61a;
+          font-size: 14px;
+          display: none;
+        }
+        #${SETTINGS_PANEL_ID} .safe-mode-banner {
+          padding: 10px;
+          margin: 10px 0;
+          border-radius: 3px;
+          background: rgba(88, 101, 242, 0.2);
+          color: var(--text-normal, #dcddde);
+          font-size: 14px;
+          display: none;
+        }
+        #${SETTINGS_PANEL_ID} .settings-warnings ul {
+          margin: 6px 0 0;
+          padding-left: 18px;
+        }
+        #${SETTINGS_PANEL_ID} input[type="text"],
+        #${SETTINGS_PANEL_ID} select {
           flex: 1;
           padding: 10px;
           background: var(--background-secondary, #2f3136);
@@ -371,10 +399,39 @@
           gap: 8px;
           margin-top: 8px;
         }
+        #${SETTINGS_PANEL_ID} .theme-row {
+          display: flex;
+          align-items: center;
+          gap: 8px;
+          padding: 8px 0;
+          border-bottom: 1px solid var(--background-modifier-accent, #4f545c);
+        }
+        #${SETTINGS_PANEL_ID} .theme-row .setting-label {
+          overflow: hidden;
+          text-overflow: ellipsis;
+          white-space: nowrap;
+        }
+        #${SETTINGS_PANEL_ID} .theme-row button {
+          padding: 4px 10px;
+        }
       </style>
 
       <h1>⚡ Ghostcord Lite</h1>
       <div id="status-message" class="status-message"></div>
+      <div id="settings-warnings" class="settings-warnings"></div>
+      <div id="safe-mode-banner" class="safe-mode-banner"></div>
+
+      <h2>Profile</h2>
+      <div class="file-input-row">
+        <select id="profile-select"></select>
+        <button class="btn-secondary" id="btn-profile-switch">Switch</button>
+        <button class="btn-secondary" id="btn-profile-delete">Delete</button>
+      </div>
+      <div class="file-input-row">
+        <input type="text" id="profile-name" placeholder="New profile name..." />
+        <button class="btn-secondary" id="btn-profile-new">New</button>
+        <button class="btn-secondary" id="btn-profile-clone">Clone Selected</button>
+      </div>
 
       <h2>Performance</h2>
       <div class="setting-row">
@@ -408,14 +465,82 @@
         </div>
       </div>
 
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Live Reload</div>
+          <div class="setting-label-desc">Re-apply themes when their files change on disk</div>
+        </div>
+        <div class="switch" id="switch-theme-reload">
+          <div class="switch-slider"></div>
+        </div>
+      </div>
+
       <div style="margin-top: 12px;">
-        <div class="setting-label-desc" style="margin-bottom: 4px;">Theme File (.theme.css)</div>
+        <div class="setting-label-desc" style="margin-bottom: 4px;">Installed themes (applied top to bottom, later ones win)</div>
+        <div id="theme-list"></div>
+        <div class="button-row">
+          <button class="btn-secondary" id="btn-browse">Add Theme...</button>
+        </div>
         <div class="file-input-row">
-          <input type="text" id="theme-path" placeholder="Select a theme file..." />
-          <button class="btn-secondary" id="btn-browse">Browse</button>
+          <input type="text" id="theme-url" placeholder="https://example.com/theme.css" />
+          <button class="btn-secondary" id="btn-theme-url">Add URL</button>
         </div>
-        <div class="setting-label-desc" style="margin-top: 8px;">Or paste CSS directly below (overrides file):</div>
+        <div class="setting-label-desc" style="margin-top: 8px;">Custom CSS (saved as custom.css in the theme list):</div>
         <textarea id="theme-css" placeholder="/* Paste custom CSS here */"></textarea>
+        <div class="setting-label-desc" style="margin-top: 8px;" id="assets-hint"></div>
+        <div class="setting-label-desc" style="margin-top: 8px;">Maximum theme size (KiB):</div>
+        <div class="file-input-row">
+          <input type="text" id="theme-max-kib" inputmode="numeric" />
+        </div>
+        <div class="setting-label-desc" style="margin-top: 8px;">Hosts themes may load from (one per line, *.example.com for subdomains):</div>
+        <textarea id="theme-allowed-hosts"></textarea>
+      </div>
+
+      <h2>Window</h2>
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Close to Tray</div>
+          <div class="setting-label-desc">Keep running in the tray when the window is closed</div>
+        </div>
+        <div class="switch" id="switch-close-tray">
+          <div class="switch-slider"></div>
+        </div>
+      </div>
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Start Minimized</div>
+          <div class="setting-label-desc">Start in the tray without showing the window</div>
+        </div>
+        <div class="switch" id="switch-start-min">
+          <div class="switch-slider"></div>
+        </div>
+      </div>
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Launch on Login</div>
+          <div class="setting-label-desc">Start Ghostcord when you log in (Linux)</div>
+        </div>
+        <div class="switch" id="switch-autostart">
+          <div class="switch-slider"></div>
+        </div>
+      </div>
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Launch Minimized</div>
+          <div class="setting-label-desc">Stay in the tray when launched on login</div>
+        </div>
+        <div class="switch" id="switch-autostart-min">
+          <div class="switch-slider"></div>
+        </div>
+      </div>
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Open discord:// Links</div>
+          <div class="setting-label-desc">Register Ghostcord as the handler for discord:// links (Linux)</div>
+        </div>
+        <div class="switch" id="switch-links">
+          <div class="switch-slider"></div>
+        </div>
       </div>
 
       <h2>Plugins</h2>
@@ -436,6 +561,23 @@
         <button class="btn-primary" id="btn-save">Save & Apply</button>
         <button class="btn-secondary" id="btn-reload">Reload Config</button>
       </div>
+
+      <h2>Backup</h2>
+      <div class="setting-row">
+        <div class="setting-label">
+          <div class="setting-label-title">Include window state</div>
+          <div class="setting-label-desc">Export and import state.json along with settings</div>
+        </div>
+        <div class="switch" id="switch-bundle-state">
+          <div class="switch-slider"></div>
+        </div>
+      </div>
+      <div class="button-row">
+        <button class="btn-secondary" id="btn-export">Export Bundle</button>
+        <button class="btn-secondary" id="btn-import">Import Bundle</button>
+        <button class="btn-primary" id="btn-import-confirm" style="display: none;">Confirm Import</button>
+      </div>
+      <div class="setting-label-desc" id="import-preview" style="margin-top: 8px; white-space: pre-line;"></div>
     `;
 
     return panel;
@@ -452,6 +594,120 @@
     }, 3000);
   }
 
+  async function loadSettingsWarnings() {
+    const box = document.getElementById('settings-warnings');
+    if (!box) return;
+    try {
+      const warnings = await window.__TAURI__.core.invoke('get_settings_warnings');
+      if (!warnings?.length) {
+        box.style.display = 'none';
+        return;
+      }
+      box.textContent = 'Some settings could not be loaded and were reset to defaults:';
+      const list = document.createElement('ul');
+      warnings.forEach((warning) => {
+        const item = document.createElement('li');
+        item.textContent = `${warning.field || 'settings'}: ${warning.message}`;
+        list.appendChild(item);
+      });
+      box.appendChild(list);
+      box.style.display = 'block';
+    } catch (err) {
+      console.warn('[Ghostcord] Failed to load settings warnings:', err);
+    }
+  }
+
+  const SAFE_MODE_LABELS = {
+    enable_theme: 'Custom Theme',
+    enable_perf_css: 'Performance Mode',
+    enable_blockers: 'Analytics Blocker',
+    enable_vencord: 'Vencord'
+  };
+
+  async function loadSafeModeToUI() {
+    const banner = document.getElementById('safe-mode-banner');
+    if (!banner) return;
+    try {
+      const status = await window.__TAURI__.core.invoke('get_safe_mode');
+      banner.innerHTML = '';
+      if (!status?.active && !status?.recovery_pin) {
+        banner.style.display = 'none';
+        return;
+      }
+      if (status.recovery_pin) appendRecoveryPin(banner, status.recovery_pin);
+      if (!status.active) {
+        banner.style.display = 'block';
+        return;
+      }
+      const why = status.reason === 'unfinished_startup'
+        ? 'the last launch did not finish starting up'
+        : 'it was started with --safe-mode';
+      const text = document.createElement('div');
+      text.textContent = `Safe mode: some settings are turned off for this session because ${why}. `
+        + 'Turn them back on one at a time to find the one causing trouble.';
+      banner.appendChild(text);
+
+      const row = document.createElement('div');
+      row.className = 'button-row';
+      status.held_back.forEach((key) => {
+        const button = document.createElement('button');
+        button.className = 'btn-secondary';
+        button.textContent = `Enable ${SAFE_MODE_LABELS[key] || key}`;
+        button.addEventListener('click', () => reenableFromSafeMode(key));
+        row.appendChild(button);
+      });
+      const all = document.createElement('button');
+      all.className = 'btn-primary';
+      all.textContent = 'Enable All';
+      all.addEventListener('click', () => reenableFromSafeMode(null));
+      row.appendChild(all);
+      banner.appendChild(row);
+      banner.style.display = 'block';
+    } catch (err) {
+      console.warn('[Ghostcord] Failed to load safe mode status:', err);
+    }
+  }
+
+  function appendRecoveryPin(banner, tag) {
+    const text = document.createElement('div');
+    text.textContent = `Vencord was rolled back and pinned to ${tag} after failed starts. `
+      + 'Keep the pin, or lift it to update Vencord again.';
+    banner.appendChild(text);
+
+    const row = document.createElement('div');
+    row.className = 'button-row';
+    [['Unpin Vencord', 'btn-primary', true], ['Keep Pin', 'btn-secondary', false]].forEach(([label, cls, unpin]) => {
+      const button = document.createElement('button');
+      button.className = cls;
+      button.textContent = label;
+      button.addEventListener('click', () => releaseRecoveryPin(unpin));
+      row.appendChild(button);
+    });
+    banner.appendChild(row);
+  }
+
+  async function releaseRecoveryPin(unpin) {
+    try {
+      await window.__TAURI__.core.invoke('release_recovery_pin', { unpin });
+      showStatus(unpin ? '✓ Vencord unpinned' : '✓ Vencord pin kept');
+      await loadConfigToUI();
+    } catch (err) {
+      console.error('[Ghostcord] Failed to release the Vencord pin:', err);
+      showStatus('Failed to update the Vencord pin: ' + err, true);
+    }
+  }
+
+  async function reenableFromSafeMode(key) {
+    try {
+      await window.__TAURI__.core.invoke('leave_safe_mode', { key });
+      showStatus(key ? `✓ ${SAFE_MODE_LABELS[key] || key} enabled` : '✓ Safe mode turned off');
+      await loadConfigToUI();
+    } catch (err) {
+      console.error('[Ghostcord] Failed to leave safe mode:', err);
+      showStatus('Failed to enable: ' + err, true);
+    }
+  }
+
   async function loadConfigToUI() {
     try {
       const cfg = await window.__TAURI__.core.invoke('load_config');
@@ -465,13 +721,20 @@
       toggleSwitch('switch-blockers', cfg.enable_blockers);
       toggleSwitch('switch-perf', cfg.enable_perf_css);
       toggleSwitch('switch-theme', cfg.enable_theme);
+      toggleSwitch('switch-theme-reload', cfg.theme_hot_reload);
       toggleSwitch('switch-vencord', cfg.enable_vencord);
-
-      const pathInput = document.getElementById('theme-path');
-      const cssInput = document.getElementById('theme-css');
-      if (pathInput) pathInput.value = cfg.theme_path || '';
-      if (cssInput) cssInput.value = cfg.theme_css || '';
-
+      toggleSwitch('switch-close-tray', cfg.close_to_tray);
+      toggleSwitch('switch-start-min', cfg.start_minimized);
+      toggleSwitch('switch-autostart', cfg.autostart);
+      toggleSwitch('switch-autostart-min', cfg.autostart_minimized);
+      toggleSwitch('switch-links', cfg.handle_discord_links);
+
+      applyThemeLimitsToUI(cfg);
+      loadCustomCssToUI();
+      loadThemesToUI();
+
+      await loadSettingsWarnings();
+      await loadSafeModeToUI();
       console.log('[Ghostcord] Config loaded to UI');
     } catch (err) {
       console.error('[Ghostcord] Failed to load config:', err);
@@ -489,12 +752,45 @@
     toggleSwitch('switch-blockers', cfg.enable_blockers);
     toggleSwitch('switch-perf', cfg.enable_perf_css);
     toggleSwitch('switch-theme', cfg.enable_theme);
+    toggleSwitch('switch-theme-reload', cfg.theme_hot_reload);
     toggleSwitch('switch-vencord', cfg.enable_vencord);
+    toggleSwitch('switch-close-tray', cfg.close_to_tray);
+    toggleSwitch('switch-start-min', cfg.start_minimized);
+    toggleSwitch('switch-autostart', cfg.autostart);
+    toggleSwitch('switch-autostart-min', cfg.autostart_minimized);
+    toggleSwitch('switch-links', cfg.handle_discord_links);
+
+    applyThemeLimitsToUI(cfg);
+    loadCustomCssToUI();
+    loadThemesToUI();
+  }
+
+  function applyThemeLimitsToUI(cfg) {
+    const maxInput = document.getElementById('theme-max-kib');
+    const hostsInput = document.getElementById('theme-allowed-hosts');
+    if (maxInput) maxInput.value = Math.round((cfg.theme_max_bytes || 0) / 1024);
+    if (hostsInput) hostsInput.value = (cfg.theme_allowed_hosts || []).join('\n');
+  }
+
+  async function loadAssetsHint() {
+    const hint = document.getElementById('assets-hint');
+    if (!hint) return;
+    try {
+      const dir = await window.__TAURI__.core.invoke('get_assets_dir');
+      hint.textContent = `Local images and fonts: put them in ${dir} and use url(ghostcord://localhost/<file>).`;
+    } catch (err) {
+      console.error('[Ghostcord] Failed to get assets dir:', err);
+    }
+  }
 
-    const pathInput = document.getElementById('theme-path');
+  async function loadCustomCssToUI() {
     const cssInput = document.getElementById('theme-css');
-    if (pathInput) pathInput.value = cfg.theme_path || '';
-    if (cssInput) cssInput.value = cfg.theme_css || '';
+    if (!cssInput) return;
+    try {
+      cssInput.value = await window.__TAURI__.core.invoke('read_custom_css');
+    } catch (err) {
+      console.error('[Ghostcord] Failed to read custom CSS:', err);
+    }
   }
 
   async function saveConfigFromUI() {
@@ -502,16 +798,35 @@
       const getSwitch = (id) => document.getElementById(id)?.classList.contains('active');
       
       const cfg = {
+        ...(window.__GHOSTCORD__.currentConfig || {}),
         enable_blockers: getSwitch('switch-blockers'),
         enable_perf_css: getSwitch('switch-perf'),
         enable_vencord: getSwitch('switch-vencord'),
         enable_theme: getSwitch('switch-theme'),
-        theme_path: document.getElementById('theme-path')?.value.trim() || null,
-        theme_css: document.getElementById('theme-css')?.value.trim() || null
+        theme_hot_reload: getSwitch('switch-theme-reload'),
+        close_to_tray: getSwitch('switch-close-tray'),
+        start_minimized: getSwitch('switch-start-min'),
+        autostart: getSwitch('switch-autostart'),
+        autostart_minimized: getSwitch('switch-autostart-min'),
+        handle_discord_links: getSwitch('switch-links'),
+        theme_max_bytes: Math.round(Number(document.getElementById('theme-max-kib')?.value || 0) * 1024),
+        theme_allowed_hosts: (document.getElementById('theme-allowed-hosts')?.value || '')
+          .split('\n')
+          .map((host) => host.trim())
+          .filter(Boolean)
       };
 
+      const errors = await window.__TAURI__.core.invoke('validate_settings', { cfg });
+      if (errors?.length) {
+        showStatus(errors.map((e) => `${e.field}: ${e.message}`).join('; '), true);
+        return;
+      }
+
       await window.__TAURI__.core.invoke('save_config', { cfg });
-      await window.__TAURI__.core.invoke('apply_config_to_main', { cfg });
+      window.__GHOSTCORD__.currentConfig = await window.__TAURI__.core.invoke('save_custom_css', {
+        css: document.getElementById('theme-css')?.value || ''
+      });
+      loadThemesToUI();
       
       showStatus('✓ Settings saved and applied!');
       console.log('[Ghostcord] Config saved:', cfg);
@@ -521,18 +836,217 @@
     }
   }
 
+  async function loadProfilesToUI() {
+    const select = document.getElementById('profile-select');
+    if (!select) return;
+    try {
+      const profiles = await window.__TAURI__.core.invoke('list_profiles');
+      select.innerHTML = '';
+      profiles.forEach((profile) => {
+        const option = document.createElement('option');
+        option.value = profile.name;
+        option.textContent = profile.active ? `${profile.name} (active)` : profile.name;
+        option.selected = profile.active;
+        select.appendChild(option);
+      });
+    } catch (err) {
+      console.error('[Ghostcord] Failed to load profiles:', err);
+    }
+  }
+
+  async function runProfileAction(command, args, message) {
+    try {
+      await window.__TAURI__.core.invoke(command, args);
+      showStatus(message);
+      await loadProfilesToUI();
+      await loadConfigToUI();
+    } catch (err) {
+      console.error(`[Ghostcord] ${command} failed:`, err);
+      showStatus('Profile action failed: ' + err, true);
+    }
+  }
+
+  function selectedProfile() {
+    return document.getElementById('profile-select')?.value;
+  }
+
+  function newProfileName() {
+    return document.getElementById('profile-name')?.value.trim();
+  }
+
+  let importPending = false;
+
+  function bundleIncludesState() {
+    return !!document.getElementById('switch-bundle-state')?.classList.contains('active');
+  }
+
+  async function exportBundleFromUI() {
+    try {
+      const path = await window.__TAURI__.core.invoke('export_bundle', {
+        includeState: bundleIncludesState()
+      });
+      if (path) showStatus('✓ Exported to ' + path);
+    } catch (err) {
+      console.error('[Ghostcord] Export failed:', err);
+      showStatus('Export failed: ' + err, true);
+    }
+  }
+
+  async function previewBundleFromUI() {
+    const preview = document.getElementById('import-preview');
+    const confirm = document.getElementById('btn-import-confirm');
+    try {
+      const info = await window.__TAURI__.core.invoke('preview_bundle_import');
+      if (!info) return;
+      const lines = [`Bundle from Ghostcord ${info.app_version}:`];
+      if (!info.changes.length) lines.push('No settings changes.');
+      info.changes.forEach((change) => lines.push(`• ${change.key} will change`));
+      info.theme_files.forEach((file) => lines.push(`• Theme will be installed to ${file}`));
+      if (info.has_state) lines.push(`• Contains window state${bundleIncludesState() ? '' : ' (skipped)'}`);
+      if (info.vencord?.pinned_tag) lines.push(`• Vencord pinned to ${info.vencord.pinned_tag}`);
+      info.warnings.forEach((w) => lines.push(`⚠ ${w.field}: ${w.message}`));
+      info.errors.forEach((e) => lines.push(`✗ ${e.field}: ${e.message}`));
+      if (preview) preview.textContent = lines.join('\n');
+
+      importPending = !info.errors.length;
+      if (confirm) confirm.style.display = importPending ? 'inline-block' : 'none';
+    } catch (err) {
+      console.error('[Ghostcord] Import preview failed:', err);
+      showStatus('Import failed: ' + err, true);
+    }
+  }
+
+  async function importBundleFromUI() {
+    if (!importPending) return;
+    // The picked file is used up either way; a retry needs a new preview.
+    importPending = false;
+    document.getElementById('btn-import-confirm').style.display = 'none';
+    try {
+      await window.__TAURI__.core.invoke('import_bundle', {
+        includeState: bundleIncludesState()
+      });
+      document.getElementById('import-preview').textContent = '';
+      showStatus('✓ Bundle imported');
+      await loadConfigToUI();
+    } catch (err) {
+      console.error('[Ghostcord] Import failed:', err);
+      showStatus('Import failed: ' + err, true);
+    }
+  }
+
   async function browseThemeFile() {
     try {
-      const picked = await window.__TAURI__.core.invoke('pick_theme_file');
-      if (picked) {
-        const pathInput = document.getElementById('theme-path');
-        if (pathInput) pathInput.value = picked;
-        console.log('[Ghostcord] Theme file selected:', picked);
+      const cfg = await window.__TAURI__.core.invoke('pick_theme_file');
+      if (cfg) {
+        window.__GHOSTCORD__.currentConfig = cfg;
+        loadThemesToUI();
+        showStatus('✓ Theme installed');
       }
     } catch (err) {
-      console.error('[Ghostcord] Failed to pick file:', err);
-      showStatus('Failed to select file', true);
+      console.error('[Ghostcord] Failed to install theme:', err);
+      showStatus('Failed to install theme: ' + err, true);
+    }
+  }
+
+  async function addThemeUrl() {
+    const input = document.getElementById('theme-url');
+    const url = input?.value.trim();
+    if (!url) return;
+    showStatus('Downloading theme...');
+    await runThemeAction('install_theme_url', { url });
+    if (input) input.value = '';
+  }
+
+  // Library changes are saved right away; the returned config replaces the
+  // panel's copy so a later Save does not undo them.
+  async function runThemeAction(command, args) {
+    try {
+      const cfg = await window.__TAURI__.core.invoke(command, args);
+      window.__GHOSTCORD__.currentConfig = cfg;
+      loadThemesToUI();
+    } catch (err) {
+      console.error(`[Ghostcord] ${command} failed:`, err);
+      showStatus('Theme action failed: ' + err, true);
+    }
+  }
+
+  async function loadThemesToUI() {
+    const list = document.getElementById('theme-list');
+    if (!list) return;
+    let themes;
+    try {
+      themes = await window.__TAURI__.core.invoke('list_themes');
+    } catch (err) {
+      console.error('[Ghostcord] Failed to list themes:', err);
+      return;
+    }
+
+    list.innerHTML = '';
+    if (!themes.length) {
+      list.innerHTML = '<div class="setting-label-desc">No themes installed.</div>';
+      return;
     }
+
+    const order = themes.filter((theme) => theme.listed).map((theme) => theme.file);
+    const button = (text, onClick, disabled = false) => {
+      const el = document.createElement('button');
+      el.className = 'btn-secondary';
+      el.textContent = text;
+      el.disabled = disabled;
+      el.addEventListener('click', onClick);
+      return el;
+    };
+
+    themes.forEach((theme) => {
+      const row = document.createElement('div');
+      row.className = 'theme-row';
+
+      const label = document.createElement('div');
+      label.className = 'setting-label';
+      const title = document.createElement('div');
+      title.className = 'setting-label-title';
+      title.textContent = theme.meta.name || theme.file;
+      const desc = document.createElement('div');
+      desc.className = 'setting-label-desc';
+      desc.textContent = theme.error
+        ? `⚠ ${theme.error}`
+        : theme.blocked.length
+        ? `⚠ Blocked ${theme.blocked.length} remote resource(s): ${theme.blocked.join(', ')}`
+        : [theme.meta.version && `v${theme.meta.version}`, theme.meta.author && `by ${theme.meta.author}`, theme.meta.description, theme.url]
+          .filter(Boolean)
+          .join(' · ') || theme.file;
+      label.title = theme.url || theme.path;
+      label.append(title, desc);
+      row.appendChild(label);
+
+      if (!theme.listed) {
+        row.appendChild(button('Add', () => runThemeAction('install_theme', { file: theme.file })));
+        list.appendChild(row);
+        return;
+      }
+
+      const toggle = document.createElement('div');
+      toggle.className = 'switch' + (theme.enabled ? ' active' : '');
+      toggle.innerHTML = '<div class="switch-slider"></div>';
+      toggle.addEventListener('click', () =>
+        runThemeAction('set_theme_enabled', { file: theme.file, enabled: !theme.enabled })
+      );
+
+      const index = order.indexOf(theme.file);
+      const move = (offset) => {
+        const next = order.slice();
+        [next[index], next[index + offset]] = [next[index + offset], next[index]];
+        runThemeAction('reorder_themes', { files: next });
+      };
+
+      row.append(
+        toggle,
+        button('↑', () => move(-1), index === 0),
+        button('↓', () => move(1), index === order.length - 1),
+        button('Remove', () => runThemeAction('remove_theme', { file: theme.file }))
+      );
+      list.appendChild(row);
+    });
   }
 
   function attachSettingsPanelHandlers() {
@@ -540,7 +1054,7 @@
     if (!panel) return;
 
     // Toggle switches
-    ['switch-blockers', 'switch-perf', 'switch-theme', 'switch-vencord'].forEach(id => {
+    ['switch-blockers', 'switch-perf', 'switch-theme', 'switch-theme-reload', 'switch-vencord', 'switch-close-tray', 'switch-start-min', 'switch-autostart', 'switch-autostart-min', 'switch-links', 'switch-bundle-state'].forEach(id => {
       const sw = document.getElementById(id);
       if (sw) {
         sw.addEventListener('click', () => {
@@ -557,6 +1071,31 @@
     if (btnSave) btnSave.addEventListener('click', saveConfigFromUI);
     if (btnReload) btnReload.addEventListener('click', loadConfigToUI);
     if (btnBrowse) btnBrowse.addEventListener('click', browseThemeFile);
+    document.getElementById('btn-theme-url')?.addEventListener('click', addThemeUrl);
+    loadAssetsHint();
+
+    document.getElementById('btn-profile-switch')?.addEventListener('click', () => {
+      const name = selectedProfile();
+      if (name) runProfileAction('switch_profile', { name }, `✓ Switched to ${name}`);
+    });
+    document.getElementById('btn-profile-delete')?.addEventListener('click', () => {
+      const name = selectedProfile();
+      if (name) runProfileAction('delete_profile', { name }, `✓ Deleted ${name}`);
+    });
+    document.getElementById('btn-profile-new')?.addEventListener('click', () => {
+      const name = newProfileName();
+      if (name) runProfileAction('create_profile', { name }, `✓ Created ${name}`);
+    });
+    document.getElementById('btn-profile-clone')?.addEventListener('click', () => {
+      const name = newProfileName();
+      const from = selectedProfile();
+      if (name && from) runProfileAction('clone_profile', { from, name }, `✓ Cloned ${from} to ${name}`);
+    });
+    loadProfilesToUI();
+
+    document.getElementById('btn-export')?.addEventListener('click', exportBundleFromUI);
+    document.getElementById('btn-import')?.addEventListener('click', previewBundleFromUI);
+    document.getElementById('btn-import-confirm')?.addEventListener('click', importBundleFromUI);
 
     // Load initial config
     loadConfigToUI();
@@ -703,9 +1242,21 @@
   }
 
 
+  function setupVencordUpdateListener() {
+    const listen = window.__TAURI__?.event?.listen;
+    if (!listen) return;
+    listen('ghostcord://vencord-update-available', (event) => {
+      ensureRuntime();
+      window.__GHOSTCORD__.vencordUpdate = event.payload;
+      console.info('[Ghostcord] Vencord update available:', event.payload?.latest);
+    });
+  }
+
+
   // Initialize
   ensureRuntime();
-  applyPerfCss();
+  setupVencordUpdateListener();
+  if (!window.__GHOSTCORD_SAFE_MODE__) applyPerfCss();
   applyBlockersFromConfig(window.__GHOSTCORD__.currentConfig);
   
   if (document.readyState === 'loading') {
@@ -0,0 +1,338 @@
+use std::{env, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::{cli, commands, deeplink, settings, windows};
+
+/// What a later launch sends to the running instance.
+#[derive(Debug, Serialize, Deserialize)]
+struct Forwarded {
+    args: Vec<String>,
+}
+
+pub enum Startup {
+    /// No other instance is running. Later launches arrive on the listener,
+    /// when one could be opened.
+    Primary(Option<Listener>),
+    /// The arguments went to the running instance; this process should exit.
+    Forwarded,
+    /// Another instance holds the lock but could not be reached.
+    Unreachable(String),
+}
+
+pub struct Listener {
+    #[cfg(unix)]
+    inner: std::os::unix::net::UnixListener,
+}
+
+/// `$XDG_RUNTIME_DIR`, or a directory of our own in the temp dir when there
+/// is none. A `--data-dir` gets its own instance.
+fn runtime_path(name: &str) -> Result<PathBuf, String> {
+    let name = match settings::data_dir_override() {
+        Some(dir) => {
+            let digest = format!("{:x}", Sha256::digest(dir.to_string_lossy().as_bytes()));
+            format!("{}.{name}", &digest[..12])
+        }
+        None => name.to_string(),
+    };
+    match env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+    {
+        Some(dir) => Ok(dir.join(format!("ghostcord-lite.{name}"))),
+        None => Ok(fallback_runtime_dir()?.join(name)),
+    }
+}
+
+/// The temp dir is shared, so names there are predictable to other users.
+/// The lock and socket go in a directory only this user can enter, which
+/// must already be ours if it exists.
+#[cfg(unix)]
+fn fallback_runtime_dir() -> Result<PathBuf, String> {
+    // SAFETY: getuid cannot fail and has no preconditions.
+    let uid = unsafe { libc::getuid() };
+    private_dir(&env::temp_dir().join(format!("ghostcord-lite-{uid}")), uid)
+}
+
+#[cfg(not(unix))]
+fn fallback_runtime_dir() -> Result<PathBuf, String> {
+    Ok(env::temp_dir())
+}
+
+/// Creates `dir` as 0700, or checks that an existing one is a real
+/// directory owned by `uid` that nobody else can use.
+#[cfg(unix)]
+fn private_dir(dir: &std::path::Path, uid: u32) -> Result<PathBuf, String> {
+    use std::{
+        fs::{self, DirBuilder},
+        io::ErrorKind,
+        os::unix::fs::{DirBuilderExt, MetadataExt},
+    };
+
+    match DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+        Err(err) => return Err(format!("{}: {err}", dir.display())),
+    }
+    let meta = fs::symlink_metadata(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(format!(
+            "{} is not a private directory of this user",
+            dir.display()
+        ));
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Hands `args` to an already running instance, or becomes the instance
+/// later launches talk to.
+pub fn acquire(args: &[String]) -> Startup {
+    match runtime_path("lock").and_then(|lock| Ok((lock, runtime_path("sock")?))) {
+        Ok((lock_path, socket)) => acquire_at(&lock_path, &socket, args),
+        Err(err) => {
+            log::warn!("Single-instance lock unavailable: {err}");
+            Startup::Primary(None)
+        }
+    }
+}
+
+#[cfg(unix)]
+mod lock {
+    use std::{fs::File, io, os::fd::AsRawFd};
+
+    /// Takes an exclusive `flock` without waiting. The kernel drops it when
+    /// the process exits, crashes included.
+    pub fn try_lock(file: &File) -> io::Result<bool> {
+        // SAFETY: flock only uses the descriptor, which `file` keeps open.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+            return Ok(true);
+        }
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+            Ok(false)
+        } else {
+            Err(err)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn forward(socket: &std::path::Path, args: &[String]) -> Result<(), String> {
+    use std::{io::Write, os::unix::net::UnixStream};
+
+    let json = serde_json::to_string(&Forwarded {
+        args: args.to_vec(),
+    })
+    .map_err(|e| e.to_string())?;
+    let mut stream = UnixStream::connect(socket).map_err(|e| e.to_string())?;
+    writeln!(stream, "{json}").map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn acquire_at(lock_path: &std::path::Path, socket: &std::path::Path, args: &[String]) -> Startup {
+    use std::{
+        fs::{self, OpenOptions},
+        os::unix::{
+            fs::{OpenOptionsExt, PermissionsExt},
+            net::UnixListener,
+        },
+        thread,
+        time::Duration,
+    };
+
+    /// The running instance may still be starting up and not listening yet.
+    const FORWARD_ATTEMPTS: u32 = 20;
+    const FORWARD_DELAY: Duration = Duration::from_millis(100);
+
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .mode(0o600)
+        .open(lock_path);
+    match lock.and_then(|file| lock::try_lock(&file).map(|locked| (file, locked))) {
+        Ok((file, true)) => {
+            let _ = fs::set_permissions(lock_path, fs::Permissions::from_mode(0o600));
+            // Held until the process exits, so the file is never closed.
+            std::mem::forget(file);
+        }
+        Ok((_, false)) => {
+            let mut last_err = String::new();
+            for _ in 0..FORWARD_ATTEMPTS {
+                match forward(socket, args) {
+                    Ok(()) => return Startup::Forwarded,
+                    Err(err) => last_err = err,
+                }
+                thread::sleep(FORWARD_DELAY);
+            }
+            return Startup::Unreachable(format!("{}: {last_err}", socket.display()));
+        }
+        Err(err) => {
+            log::warn!(
+                "Single-instance lock {} unavailable: {err}",
+                lock_path.display()
+            );
+        }
+    }
+
+    // Holding the lock means any socket left behind belongs to a crashed
+    // instance.
+    let _ = fs::remove_file(socket);
+    match UnixListener::bind(socket) {
+        Ok(inner) => {
+            let _ = fs::set_permissions(socket, fs::Permissions::from_mode(0o600));
+            Startup::Primary(Some(Listener { inner }))
+        }
+        Err(err) => {
+            log::warn!("Single-instance socket {} unavailable: {err}", socket.display());
+            Startup::Primary(None)
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn acquire_at(
+    _lock_path: &std::path::Path,
+    _socket: &std::path::Path,
+    _args: &[String],
+) -> Startup {
+    Startup::Primary(None)
+}
+
+/// Handles launches forwarded by later processes for the rest of the run.
+pub fn serve(app: &AppHandle, listener: Listener) {
+    #[cfg(unix)]
+    {
+        use std::io::{BufRead, BufReader};
+
+        let app = app.clone();
+        std::thread::spawn(move || {
+            for stream in listener.inner.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let mut line = String::new();
+                if BufReader::new(stream).read_line(&mut line).is_err() {
+                    continue;
+                }
+                match serde_json::from_str::<Forwarded>(&line) {
+                    Ok(message) => handle(&app, &message.args),
+                    Err(err) => log::warn!("Ignoring bad instance message: {err}"),
+                }
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    let _ = (app, listener);
+}
+
+fn handle(app: &AppHandle, args: &[String]) {
+    let launch = match cli::parse(args) {
+        Ok(cli) if cli.command == cli::Command::Run => cli.options,
+        Ok(cli) => {
+            log::warn!("Ignoring forwarded {:?} command", cli.command);
+            return;
+        }
+        Err(err) => {
+            log::warn!("Ignoring forwarded launch: {err}");
+            return;
+        }
+    };
+    log::info!("Another launch forwarded {launch:?}");
+    if launch.no_vencord || launch.safe_mode {
+        log::warn!("--no-vencord and --safe-mode only apply when Ghostcord starts");
+    }
+
+    if let Some(profile) = launch.profile {
+        if profile != settings::active_profile(app) {
+            let store = app.state::<settings::SettingsStore>();
+            if let Err(err) = commands::switch_profile(app.clone(), store, profile) {
+                log::warn!("Failed to switch to forwarded profile: {err}");
+            }
+        }
+    }
+
+    // A link is opened even when asked to stay minimized.
+    if !launch.start_minimized || launch.route.is_some() {
+        windows::show_main(app);
+    }
+    if launch.devtools {
+        if let Some(window) = app.get_webview_window("main") {
+            window.open_devtools();
+        }
+    }
+    if let Some(route) = launch.route {
+        if let Err(err) = deeplink::navigate(app, &route) {
+            log::warn!("Failed to open forwarded link: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn second_launch_forwards_args() {
+        use std::io::{BufRead, BufReader};
+
+        let base = env::temp_dir().join(format!("ghostcord-instance-{}", std::process::id()));
+        let lock_path = base.with_extension("lock");
+        let socket = base.with_extension("sock");
+        // Left over from a crashed instance.
+        std::fs::write(&socket, b"").unwrap();
+
+        let Startup::Primary(Some(listener)) = acquire_at(&lock_path, &socket, &[]) else {
+            panic!("first launch should own the lock");
+        };
+        let forwarded = args(&["ghostcord-lite", "discord://-/channels/@me"]);
+        assert!(matches!(
+            acquire_at(&lock_path, &socket, &forwarded),
+            Startup::Forwarded
+        ));
+
+        let (stream, _) = listener.inner.accept().unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        assert_eq!(serde_json::from_str::<Forwarded>(&line).unwrap().args, forwarded);
+
+        let _ = std::fs::remove_file(&socket);
+        let _ = std::fs::remove_file(&lock_path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fallback_dir_must_be_private_and_ours() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let base = env::temp_dir().join(format!("ghostcord-runtime-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(&base).unwrap();
+        // SAFETY: getuid cannot fail and has no preconditions.
+        let uid = unsafe { libc::getuid() };
+
+        let dir = base.join("fresh");
+        assert_eq!(private_dir(&dir, uid).unwrap(), dir);
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(private_dir(&dir, uid).is_ok());
+        assert!(private_dir(&dir, uid + 1).is_err());
+
+        let open = base.join("open");
+        fs::create_dir(&open).unwrap();
+        fs::set_permissions(&open, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(private_dir(&open, uid).is_err());
+
+        let link = base.join("link");
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        assert!(private_dir(&link, uid).is_err());
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -1,29 +1,122 @@
 use tauri::{ipc::Invoke, AppHandle, Manager};
 
-use crate::commands;
+use crate::{autostart, commands, deeplink, safe_mode, settings::SettingsStore, themes, vencord};
 
 pub fn handler() -> impl Fn(Invoke<tauri::Wry>) -> bool + Send + Sync {
     tauri::generate_handler![
         commands::get_settings,
         commands::set_settings,
+        commands::get_settings_warnings,
+        commands::validate_settings,
+        commands::get_safe_mode,
+        commands::leave_safe_mode,
+        commands::release_recovery_pin,
         commands::load_config,
         commands::save_config,
         commands::pick_theme_file,
+        commands::list_themes,
+        commands::install_theme,
+        commands::install_theme_url,
+        commands::refresh_themes,
+        commands::get_assets_dir,
+        commands::read_custom_css,
+        commands::save_custom_css,
+        commands::remove_theme,
+        commands::set_theme_enabled,
+        commands::reorder_themes,
         commands::apply_config_to_main,
+        commands::list_profiles,
+        commands::create_profile,
+        commands::clone_profile,
+        commands::delete_profile,
+        commands::switch_profile,
+        commands::export_bundle,
+        commands::preview_bundle_import,
+        commands::import_bundle,
         commands::load_state,
         commands::save_state,
         commands::ensure_vencord_assets,
         commands::apply_vencord_to_main,
+        commands::check_vencord_update,
+        commands::update_vencord,
+        commands::cancel_vencord_download,
+        commands::list_vencord_versions,
+        commands::rollback_vencord,
     ]
 }
 
 pub fn apply_initial_config(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    if let Ok(cfg) = commands::load_config(
-        app.clone(),
-        app.state::<crate::settings::SettingsStore>(),
-    ) {
+    if let Ok(cfg) = commands::load_config(app.clone(), app.state::<SettingsStore>()) {
         let _ = commands::apply_config_to_main(app.clone(), cfg);
     }
 
     Ok(())
 }
+
+/// Lets subsystems react to the settings they own once the initial config
+/// has been applied.
+pub fn watch_settings(app: &AppHandle) {
+    let store = app.state::<SettingsStore>();
+
+    let handle = app.clone();
+    store.subscribe(
+        &[
+            "enable_theme",
+            "enable_blockers",
+            "enable_perf_css",
+            "enable_vencord",
+            "themes",
+            "theme_max_bytes",
+            "theme_allowed_hosts",
+        ],
+        move |_, cfg| {
+            if let Err(err) = commands::apply_config_to_main(handle.clone(), cfg.clone()) {
+                log::warn!("Failed to apply settings change: {err}");
+            }
+        },
+    );
+
+    let handle = app.clone();
+    store.subscribe(&["enable_theme", "themes"], move |_, _| {
+        themes::refresh_remote_in_background(&handle);
+    });
+
+    store.subscribe(&["autostart", "autostart_minimized"], |_, cfg| {
+        if let Err(err) = autostart::sync(cfg) {
+            log::warn!("Failed to update autostart entry: {err}");
+        }
+    });
+
+    store.subscribe(&["handle_discord_links"], |_, cfg| {
+        if let Err(err) = deeplink::sync_protocol_handler(cfg) {
+            log::warn!("Failed to update discord:// handler: {err}");
+        }
+    });
+
+    let handle = app.clone();
+    store.subscribe(&["vencord_pin"], move |_, cfg| {
+        safe_mode::pin_changed(&handle, cfg.vencord_pin.as_deref());
+    });
+
+    let handle = app.clone();
+    store.subscribe(&["vencord_pin", "vencord_source"], move |change, cfg| {
+        if !cfg.enable_vencord {
+            return;
+        }
+        log::info!(
+            "Vencord settings changed ({}), syncing install",
+            change
+                .changed
+                .iter()
+                .map(|field| field.key.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = vencord::sync_vencord_assets(&handle).await {
+                log::warn!("Vencord sync after settings change failed: {err}");
+            }
+        });
+    });
+}
@@ -1,14 +1,49 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod autostart;
+mod bundle;
+mod cli;
 mod commands;
 mod config;
+mod deeplink;
+mod instance;
 mod ipc;
+mod migrate;
+mod persist;
+mod protocols;
+mod recovery;
+mod safe_mode;
 mod settings;
+mod splash;
 mod state;
+mod theme_cache;
+mod theme_watcher;
+mod themes;
+mod tray;
 mod vencord;
 mod windows;
 
 fn main() {
-    app::run();
+    let args: Vec<String> = std::env::args().collect();
+    let cli = match cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("ghostcord-lite: {err}\nTry 'ghostcord-lite --help' for more information.");
+            std::process::exit(2);
+        }
+    };
+    if let Some(dir) = &cli.options.data_dir {
+        settings::set_data_dir_override(dir.clone());
+    }
+
+    match cli.command {
+        cli::Command::Run => app::run(cli.options, &args),
+        command => {
+            if let Err(err) = cli::run_headless(command, &cli.options) {
+                eprintln!("ghostcord-lite: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
 }
@@ -0,0 +1,65 @@
+use serde_json::{Map, Value};
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a raw document by exactly one schema version, in place. The
+/// migration at index `n` takes a document from version `n` to `n + 1`.
+pub type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+pub fn schema_version(value: &Value) -> Result<u64, String> {
+    match value.get(SCHEMA_VERSION_KEY) {
+        None | Some(Value::Null) => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format!("invalid {SCHEMA_VERSION_KEY}: {version}")),
+    }
+}
+
+/// Runs every migration from the document's `schema_version` (0 when
+/// missing) up to the latest one. Documents from a newer build are left
+/// untouched. Returns whether any migration ran.
+pub fn run(value: &mut Value, migrations: &[Migration]) -> Result<bool, String> {
+    let latest = migrations.len() as u64;
+    let version = schema_version(value)?;
+    if version > latest {
+        log::warn!("Schema version {version} is newer than supported {latest}; loading as-is");
+        return Ok(false);
+    }
+
+    let object = value
+        .as_object_mut()
+        .ok_or("expected a JSON object at the top level")?;
+    for (from, migration) in migrations.iter().enumerate().skip(version as usize) {
+        let to = from as u64 + 1;
+        migration(object).map_err(|e| format!("migration v{from} -> v{to} failed: {e}"))?;
+        object.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(to));
+    }
+    Ok(version < latest)
+}
+
+/// Drops `schema_version` from a loaded document, unless a newer build
+/// wrote it: then it stays with the unknown keys for [`stamp`] to keep. A
+/// document whose migration failed should keep it the same way.
+pub fn strip_version(object: &mut Map<String, Value>, migrations: &[Migration]) {
+    let newer = object
+        .get(SCHEMA_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .is_some_and(|version| version > migrations.len() as u64);
+    if !newer {
+        object.remove(SCHEMA_VERSION_KEY);
+    }
+}
+
+/// Marks a freshly serialized document as being at the latest version. One
+/// that kept its version keeps it: a newer build's, so that build does not
+/// migrate its own data again after a downgrade, or an older one whose
+/// migration failed, so it is tried again.
+pub fn stamp(value: &mut Value, migrations: &[Migration]) {
+    if let Some(object) = value.as_object_mut() {
+        let version = object
+            .get(SCHEMA_VERSION_KEY)
+            .and_then(Value::as_u64)
+            .unwrap_or(migrations.len() as u64);
+        object.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(version));
+    }
+}
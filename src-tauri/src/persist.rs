@@ -0,0 +1,214 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// True if `path` or its backup exists, i.e. [`read_json`] has something
+/// to read.
+pub fn exists(path: &Path) -> bool {
+    path.exists() || backup_path(path).exists()
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Err(err) = File::open(dir).and_then(|dir| dir.sync_all()) {
+            log::warn!("Failed to sync {}: {err}", dir.display());
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) {}
+
+fn create_temp(path: &Path, private: bool) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    options.open(path)
+}
+
+/// Writes `bytes` to a temp file next to `path`, fsyncs it and renames it
+/// into place, so readers only ever see the old or the new contents.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    replace_file(path, bytes, false)
+}
+
+/// [`write_atomic`] for files only the owner may read.
+pub fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    replace_file(path, bytes, true)
+}
+
+fn replace_file(path: &Path, bytes: &[u8], private: bool) -> Result<(), String> {
+    let temp = temp_path(path);
+    let result = create_temp(&temp, private)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temp);
+        return Err(format!("failed to write {}: {err}", path.display()));
+    }
+    sync_dir(path);
+    Ok(())
+}
+
+/// Atomically replaces a JSON file, first copying the current file to
+/// `<name>.bak` if it still parses.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+
+    if let Ok(current) = fs::read(path) {
+        if serde_json::from_slice::<serde_json::Value>(&current).is_ok() {
+            if let Err(err) = write_atomic(&backup_path(path), &current) {
+                log::warn!("Failed to back up {}: {err}", path.display());
+            }
+        }
+    }
+
+    write_atomic(path, json.as_bytes())
+}
+
+/// Reads a JSON file written by [`write_json`]. If it is unreadable or not
+/// valid JSON, the `.bak` copy is used instead and restored over the
+/// original. Valid JSON of the wrong shape is an error and the file is left
+/// as it is, for a more tolerant reader to make sense of.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let err = match fs::read(path) {
+        Ok(bytes) => match serde_json::from_slice::<T>(&bytes) {
+            Ok(value) => return Ok(value),
+            Err(err) if err.is_data() => return Err(err.to_string()),
+            Err(err) => err.to_string(),
+        },
+        Err(err) if err.kind() == ErrorKind::NotFound && !backup_path(path).exists() => {
+            return Err(err.to_string());
+        }
+        Err(err) => err.to_string(),
+    };
+
+    let backup = backup_path(path);
+    let bytes = fs::read(&backup).map_err(|_| err.clone())?;
+    let value = serde_json::from_slice::<T>(&bytes).map_err(|_| err.clone())?;
+
+    log::warn!(
+        "Recovered {} from backup after read failure: {err}",
+        path.display()
+    );
+    if let Err(err) = write_atomic(path, &bytes) {
+        log::warn!("Failed to restore {} from backup: {err}", path.display());
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ghostcord-persist-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn atomic_write_replaces_without_leftovers() {
+        let dir = scratch_dir("atomic");
+        let path = dir.join("file.txt");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(names, ["file.txt"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn json_writes_back_up_the_previous_valid_file() {
+        let dir = scratch_dir("backup");
+        let path = dir.join("settings.json");
+        write_json(&path, &json!({ "n": 1 })).unwrap();
+        assert!(!backup_path(&path).exists());
+
+        write_json(&path, &json!({ "n": 2 })).unwrap();
+        let backup: Value = serde_json::from_slice(&fs::read(backup_path(&path)).unwrap()).unwrap();
+        assert_eq!(backup, json!({ "n": 1 }));
+
+        // A broken file is not worth keeping over the last good backup.
+        fs::write(&path, b"{ \"n\": ").unwrap();
+        write_json(&path, &json!({ "n": 3 })).unwrap();
+        let backup: Value = serde_json::from_slice(&fs::read(backup_path(&path)).unwrap()).unwrap();
+        assert_eq!(backup, json!({ "n": 1 }));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn truncated_file_is_recovered_from_backup() {
+        let dir = scratch_dir("recover");
+        let path = dir.join("state.json");
+        write_json(&path, &json!({ "n": 1 })).unwrap();
+        write_json(&path, &json!({ "n": 2 })).unwrap();
+        fs::write(&path, b"{ \"n\": 2").unwrap();
+
+        assert_eq!(read_json::<Value>(&path).unwrap(), json!({ "n": 1 }));
+        let restored: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(restored, json!({ "n": 1 }));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn wrong_shape_is_not_replaced_by_backup() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Counter {
+            #[allow(dead_code)]
+            n: u32,
+        }
+
+        let dir = scratch_dir("shape");
+        let path = dir.join("manifest.json");
+        write_json(&path, &json!({ "n": 1 })).unwrap();
+        write_json(&path, &json!({ "n": "two" })).unwrap();
+
+        assert!(read_json::<Counter>(&path).is_err());
+        assert_eq!(read_json::<Value>(&path).unwrap(), json!({ "n": "two" }));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    borrow::Cow,
+    fs,
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use tauri::{
+    http::{header, Method, Request, Response, StatusCode},
+    AppHandle, UriSchemeContext, UriSchemeResponder, Url, Wry,
+};
+
+use crate::settings;
+
+/// `ghostcord://localhost/<path>` (`http://ghostcord.localhost/<path>` on
+/// Windows) serves `<path>` from the user assets dir, so themes can use
+/// local images and fonts. Paths under `/_ghostcord/` are pages built into
+/// the binary instead.
+pub const SCHEME: &str = "ghostcord";
+
+const BUNDLED_PREFIX: &str = "/_ghostcord/";
+const BUNDLED: &[(&str, &[u8])] = &[("splash.html", include_bytes!("splash/splash.html"))];
+
+/// URL of a page built into the binary, for loading into a webview.
+pub fn bundled_url(name: &str) -> Result<Url, String> {
+    let base = if cfg!(windows) {
+        format!("http://{SCHEME}.localhost")
+    } else {
+        format!("{SCHEME}://localhost")
+    };
+    Url::parse(&format!("{base}{BUNDLED_PREFIX}{name}")).map_err(|e| e.to_string())
+}
+
+fn bundled(name: &str) -> Option<&'static [u8]> {
+    BUNDLED
+        .iter()
+        .find(|(bundled, _)| *bundled == name)
+        .map(|(_, bytes)| *bytes)
+}
+
+pub fn assets_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = settings::data_dir(app)?.join("assets");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+pub fn handler(
+) -> impl Fn(UriSchemeContext<'_, Wry>, Request<Vec<u8>>, UriSchemeResponder) + Send + Sync + 'static
+{
+    |ctx, request, responder| {
+        let app = ctx.app_handle().clone();
+        // Fonts and images can be large; keep file reads off the main thread.
+        tauri::async_runtime::spawn_blocking(move || {
+            responder.respond(respond(&app, &request));
+        });
+    }
+}
+
+fn respond(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    if let Some(name) = request.uri().path().strip_prefix(BUNDLED_PREFIX) {
+        return respond_bundled(name);
+    }
+    let dir = match assets_dir(app) {
+        Ok(dir) => dir,
+        Err(err) => {
+            log::warn!("Assets dir unavailable: {err}");
+            return status(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let Some(path) = resolve(&dir, request.uri().path()) else {
+        log::warn!("Rejected asset request {}", request.uri());
+        return status(StatusCode::FORBIDDEN);
+    };
+    let Some(meta) = fs::metadata(&path).ok().filter(|meta| meta.is_file()) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let etag = format!("\"{:x}-{:x}\"", meta.len(), modified);
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime_type(&path))
+        .header(header::ETAG, etag.as_str())
+        // Assets are edited in place, so always revalidate.
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+
+    let not_modified = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    if not_modified {
+        return builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Cow::Borrowed(&[][..]))
+            .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    let body = if request.method() == Method::HEAD {
+        Vec::new()
+    } else {
+        match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                log::warn!("Failed to read {}: {err}", path.display());
+                return status(StatusCode::NOT_FOUND);
+            }
+        }
+    };
+    builder
+        .header(header::CONTENT_LENGTH, meta.len())
+        .body(Cow::Owned(body))
+        .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+fn respond_bundled(name: &str) -> Response<Cow<'static, [u8]>> {
+    let Some(bytes) = bundled(name) else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, mime_type(Path::new(name)))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Cow::Borrowed(bytes))
+        .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR))
+}
+
+fn status(code: StatusCode) -> Response<Cow<'static, [u8]>> {
+    let mut response = Response::new(Cow::Borrowed(&[][..]));
+    *response.status_mut() = code;
+    response
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Maps a request path onto a file inside `dir`. Anything that could step
+/// outside it, including symlinks pointing elsewhere, gives `None`.
+fn resolve(dir: &Path, request_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(request_path)?;
+    let relative = Path::new(decoded.trim_start_matches('/'));
+    if decoded.contains(['\\', '\0']) {
+        return None;
+    }
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let path = dir.join(relative);
+    match (path.canonicalize(), dir.canonicalize()) {
+        (Ok(path), Ok(dir)) if path.starts_with(&dir) => Some(path),
+        // Missing files are a 404, not a traversal attempt.
+        (Err(_), _) => Some(path),
+        _ => None,
+    }
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "css" => "text/css; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_rejects_traversal() {
+        let dir = std::env::temp_dir().join(format!("ghostcord-assets-{}", std::process::id()));
+        fs::create_dir_all(dir.join("fonts")).unwrap();
+        fs::write(dir.join("fonts/Inter Bold.woff2"), b"font").unwrap();
+
+        assert_eq!(
+            resolve(&dir, "/fonts/Inter%20Bold.woff2"),
+            Some(dir.join("fonts/Inter Bold.woff2").canonicalize().unwrap())
+        );
+        assert_eq!(resolve(&dir, "/missing.png"), Some(dir.join("missing.png")));
+        assert_eq!(resolve(&dir, "/../secret"), None);
+        assert_eq!(resolve(&dir, "/fonts/%2e%2e/%2e%2e/secret"), None);
+        assert_eq!(resolve(&dir, "/fonts%5c..%5csecret"), None);
+        // Extra leading slashes still stay inside the dir.
+        assert_eq!(resolve(&dir, "//etc/passwd"), Some(dir.join("etc/passwd")));
+        assert_eq!(resolve(&dir, "/bad%zz"), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn mime_types_follow_extension() {
+        assert_eq!(mime_type(Path::new("a/BG.PNG")), "image/png");
+        assert_eq!(mime_type(Path::new("font.woff2")), "font/woff2");
+        assert_eq!(mime_type(Path::new("noext")), "application/octet-stream");
+    }
+
+    #[test]
+    fn bundled_pages_are_reachable() {
+        let url = bundled_url("splash.html").unwrap();
+        let name = url.path().strip_prefix(BUNDLED_PREFIX).unwrap();
+        assert!(bundled(name).is_some());
+        assert!(bundled("missing.html").is_none());
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::{config::AppConfig, persist, settings, vencord};
+
+const LAST_GOOD_FILE: &str = "last_good.json";
+
+/// What the last launch that started cleanly ran with, nothing held back.
+#[derive(Debug, Serialize, Deserialize)]
+struct LastGood {
+    launch: u64,
+    profile: String,
+    /// The profile's settings file as it was, schema version included.
+    settings: Value,
+    /// Active Vencord install id.
+    vencord: Option<String>,
+}
+
+fn last_good_path(app: &AppHandle) -> Result<PathBuf, String> {
+    settings::data_dir(app).map(|dir| dir.join(LAST_GOOD_FILE))
+}
+
+/// Remembers the settings on disk and the Vencord install of `launch` as
+/// known to work.
+pub fn record_good(app: &AppHandle, launch: u64) -> Result<(), String> {
+    let profile = settings::active_profile(app);
+    let snapshot = LastGood {
+        launch,
+        settings: settings::read_profile(app, &profile)?.to_versioned()?,
+        profile,
+        vencord: vencord::active_install(app)?,
+    };
+    persist::write_json(&last_good_path(app)?, &snapshot)
+}
+
+/// What [`roll_back`] restored.
+pub struct RolledBack {
+    pub launch: u64,
+    /// Release tag Vencord was pinned to, when the rollback set the pin.
+    pub pinned: Option<String>,
+}
+
+/// Puts back the settings and Vencord install of the last good launch.
+/// Vencord is pinned to the restored release so the next start does not
+/// update past it again.
+pub fn roll_back(app: &AppHandle) -> Result<RolledBack, String> {
+    let path = last_good_path(app)?;
+    if !persist::exists(&path) {
+        return Err("no launch has finished starting up yet".to_string());
+    }
+    let snapshot = persist::read_json::<LastGood>(&path)?;
+    let mut cfg: AppConfig = AppConfig::from_versioned(snapshot.settings).config;
+
+    let mut pinned = None;
+    if let Some(id) = &snapshot.vencord {
+        match vencord::activate_install(app, id) {
+            Ok(tag) if cfg.enable_vencord && cfg.vencord_pin.is_none() => {
+                cfg.vencord_pin = Some(tag.clone());
+                pinned = Some(tag);
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("Could not restore Vencord {id}: {err}"),
+        }
+    }
+    settings::restore_profile(app, &snapshot.profile, &cfg)?;
+    Ok(RolledBack {
+        launch: snapshot.launch,
+        pinned,
+    })
+}
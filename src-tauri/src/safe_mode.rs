@@ -0,0 +1,264 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{recovery, settings, state};
+
+/// Everything safe mode starts without.
+pub const SAFE_MODE_KEYS: &[&str] = &[
+    "enable_theme",
+    "enable_perf_css",
+    "enable_blockers",
+    "enable_vencord",
+];
+
+/// Themes and Vencord go in after the page has loaded, so a crash they cause
+/// this soon afterwards still counts as a failed start.
+const STARTUP_GRACE: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeModeReason {
+    /// `--safe-mode` on the command line.
+    Requested,
+    /// The previous launch never finished starting up.
+    UnfinishedStartup,
+}
+
+#[derive(Default)]
+pub struct SafeMode {
+    reason: Mutex<Option<SafeModeReason>>,
+    startup_done: AtomicBool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SafeModeStatus {
+    pub active: bool,
+    pub reason: Option<SafeModeReason>,
+    /// Settings that are on but skipped for this run.
+    pub held_back: Vec<&'static str>,
+    /// Vencord release a rollback pinned, while the pin is still set.
+    pub recovery_pin: Option<String>,
+}
+
+/// Failed starts in a row after which the last good settings are restored,
+/// for when safe mode alone did not get Ghostcord started.
+const ROLLBACK_AFTER: u64 = 3;
+
+#[derive(Debug, PartialEq)]
+enum Recovery {
+    None,
+    SafeMode,
+    RollBack,
+}
+
+/// What to do after `failed` launches in a row never finished starting. A
+/// rollback is tried once per streak; safe mode takes over again if the
+/// restored settings fail too.
+fn recovery_for(failed: u64) -> Recovery {
+    match failed {
+        0 => Recovery::None,
+        ROLLBACK_AFTER => Recovery::RollBack,
+        _ => Recovery::SafeMode,
+    }
+}
+
+/// Counts the launch and recovers from earlier ones that crashed before
+/// finishing: safe mode, or a rollback to the last good settings and
+/// Vencord version. Must run after the state is loaded and before the
+/// settings are.
+pub fn begin_launch(app: &AppHandle, requested: bool) {
+    let state_store = app.state::<state::StateStore>();
+    let failed = state_store.get().failed_launches();
+    if failed > 0 {
+        log::warn!("Launches in a row that did not finish starting up: {failed}");
+    }
+
+    let mut reason = requested.then_some(SafeModeReason::Requested);
+    let mut recovery_pin = None;
+    match recovery_for(failed) {
+        Recovery::None => {}
+        Recovery::RollBack if !requested => match recovery::roll_back(app) {
+            Ok(rolled_back) => {
+                log::warn!(
+                    "Restored the settings and Vencord version of launch {} after {failed} failed starts",
+                    rolled_back.launch
+                );
+                recovery_pin = rolled_back.pinned;
+            }
+            Err(err) => {
+                log::warn!("Cannot restore the last good settings: {err}");
+                reason = Some(SafeModeReason::UnfinishedStartup);
+            }
+        },
+        Recovery::SafeMode | Recovery::RollBack => {
+            reason = reason.or(Some(SafeModeReason::UnfinishedStartup));
+        }
+    }
+    if let Some(reason) = reason {
+        log::info!("Starting in safe mode ({reason:?})");
+        app.state::<settings::SettingsStore>()
+            .disable_for_session(SAFE_MODE_KEYS);
+        *app.state::<SafeMode>().reason.lock().unwrap() = Some(reason);
+    }
+
+    // Read again, a rollback may have switched profiles.
+    let mut app_state = state_store.get();
+    app_state.launch_count += 1;
+    if recovery_pin.is_some() {
+        app_state.recovery_pin = recovery_pin;
+    }
+    if let Err(err) = state::save_state(app, &state_store, app_state) {
+        log::warn!("Failed to record launch: {err}");
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<SafeMode>().reason.lock().unwrap().is_some()
+}
+
+/// Called whenever the main page finishes loading; the first time, the
+/// launch counts as finished once `STARTUP_GRACE` has passed.
+pub fn page_loaded(app: &AppHandle) {
+    if app
+        .state::<SafeMode>()
+        .startup_done
+        .swap(true, Ordering::SeqCst)
+    {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_GRACE).await;
+        finish_launch(&app, true);
+    });
+}
+
+/// A clean exit after the page loaded is not a failed start, however soon
+/// it comes, but it is not proof the settings work either.
+pub fn exiting(app: &AppHandle) {
+    if app.state::<SafeMode>().startup_done.load(Ordering::SeqCst) {
+        finish_launch(app, false);
+    }
+}
+
+fn finish_launch(app: &AppHandle, healthy: bool) {
+    let state_store = app.state::<state::StateStore>();
+    let mut app_state = state_store.get();
+    if app_state.last_finished_launch == app_state.launch_count {
+        return;
+    }
+    let launch = app_state.launch_count;
+    app_state.last_finished_launch = launch;
+    if let Err(err) = state::save_state(app, &state_store, app_state) {
+        log::warn!("Failed to record finished launch: {err}");
+    }
+
+    // A start with settings held back says nothing about those settings.
+    let store = app.state::<settings::SettingsStore>();
+    if healthy && store.held_back().is_empty() {
+        if let Err(err) = recovery::record_good(app, launch) {
+            log::warn!("Failed to remember the last good settings: {err}");
+        }
+    }
+}
+
+pub fn status(app: &AppHandle) -> SafeModeStatus {
+    let reason = *app.state::<SafeMode>().reason.lock().unwrap();
+    let store = app.state::<settings::SettingsStore>();
+    let held_back = store.held_back();
+    let recovery_pin = app
+        .state::<state::StateStore>()
+        .get()
+        .recovery_pin
+        .filter(|tag| store.get().vencord_pin.as_ref() == Some(tag));
+    SafeModeStatus {
+        active: reason.is_some() && !held_back.is_empty(),
+        reason,
+        held_back,
+        recovery_pin,
+    }
+}
+
+/// Stops reporting the pin a rollback set. With `unpin`, the pin is lifted
+/// too, so Vencord updates on the next sync.
+pub fn release_recovery_pin(app: &AppHandle, unpin: bool) -> Result<SafeModeStatus, String> {
+    let state_store = app.state::<state::StateStore>();
+    let mut app_state = state_store.get();
+    let Some(tag) = app_state.recovery_pin.take() else {
+        return Ok(status(app));
+    };
+
+    let store = app.state::<settings::SettingsStore>();
+    let mut cfg = store.get();
+    if unpin && cfg.vencord_pin.as_ref() == Some(&tag) {
+        cfg.vencord_pin = None;
+        settings::save_settings(app, &store, cfg)?;
+        log::info!("Lifted the Vencord pin to {tag} set by a rollback");
+    }
+    state::save_state(app, &state_store, app_state)?;
+    Ok(status(app))
+}
+
+/// A pin the user changed is theirs, no longer the rollback's.
+pub fn pin_changed(app: &AppHandle, pin: Option<&str>) {
+    let state_store = app.state::<state::StateStore>();
+    let mut app_state = state_store.get();
+    if app_state.recovery_pin.is_none() || app_state.recovery_pin.as_deref() == pin {
+        return;
+    }
+    app_state.recovery_pin = None;
+    if let Err(err) = state::save_state(app, &state_store, app_state) {
+        log::warn!("Failed to forget the rollback's Vencord pin: {err}");
+    }
+}
+
+/// Turns one held-back setting back on, or all of them without a key. The
+/// change is applied like any other settings change.
+pub fn reenable(app: &AppHandle, key: Option<&str>) -> Result<SafeModeStatus, String> {
+    let store = app.state::<settings::SettingsStore>();
+    let held_back = store.held_back();
+    let keys = match key {
+        Some(key) if held_back.contains(&key) => vec![key],
+        Some(key) => return Err(format!("{key} is not turned off for this session")),
+        None => held_back.clone(),
+    };
+
+    let mut cfg = store.get();
+    for key in &keys {
+        if let Some(value) = cfg.injection_mut(key) {
+            *value = true;
+        }
+    }
+    settings::save_settings(app, &store, cfg)?;
+    log::info!("Re-enabled {} after safe mode", keys.join(", "));
+    Ok(status(app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_back_once_per_streak_of_failed_starts() {
+        let steps: Vec<Recovery> = (0..=5).map(recovery_for).collect();
+        assert_eq!(
+            steps,
+            [
+                Recovery::None,
+                Recovery::SafeMode,
+                Recovery::SafeMode,
+                Recovery::RollBack,
+                Recovery::SafeMode,
+                Recovery::SafeMode,
+            ]
+        );
+    }
+}
@@ -1,102 +1,634 @@
-use std::{fs, path::PathBuf, sync::Mutex};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
 
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tauri::{AppHandle, Emitter, Manager};
 
-use crate::config::AppConfig;
+use crate::{
+    config::{AppConfig, FieldError, LoadWarning, LoadedConfig},
+    persist, state, themes,
+};
 
 pub const SETTINGS_CHANGED_EVENT: &str = "ghostcord://settings-changed";
+pub const DEFAULT_PROFILE: &str = "default";
+/// Matches `identifier` in `tauri.conf.json`, for code that runs without an
+/// app handle.
+pub const APP_IDENTIFIER: &str = "com.ghostcord.lite";
 
-pub struct SettingsStore(pub Mutex<AppConfig>);
+/// Set by `--data-dir`; replaces the platform data dir for the whole run.
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub key: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// The top-level settings keys that differ between two configs.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SettingsChange {
+    pub changed: Vec<FieldChange>,
+}
+
+impl SettingsChange {
+    pub fn between(old: &AppConfig, new: &AppConfig) -> Self {
+        let as_map = |cfg: &AppConfig| match serde_json::to_value(cfg) {
+            Ok(Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let old = as_map(old);
+        let new = as_map(new);
+
+        let mut keys = old.keys().chain(new.keys()).cloned().collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        let changed = keys
+            .into_iter()
+            .filter_map(|key| {
+                let old = old.get(&key).cloned().unwrap_or_default();
+                let new = new.get(&key).cloned().unwrap_or_default();
+                (old != new).then_some(FieldChange { key, old, new })
+            })
+            .collect();
+        Self { changed }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    pub fn touches(&self, keys: &[&str]) -> bool {
+        self.changed
+            .iter()
+            .any(|change| keys.contains(&change.key.as_str()))
+    }
+}
+
+type Listener = dyn Fn(&SettingsChange, &AppConfig) + Send + Sync;
+
+struct Subscription {
+    keys: &'static [&'static str],
+    listener: Arc<Listener>,
+}
+
+pub struct SettingsStore {
+    config: Mutex<AppConfig>,
+    warnings: Mutex<Vec<LoadWarning>>,
+    subscriptions: Mutex<Vec<Subscription>>,
+    /// Injection switches turned off for this run only, with the value each
+    /// has on disk.
+    session_off: Mutex<Vec<(&'static str, bool)>>,
+}
 
 impl SettingsStore {
     pub fn new() -> Self {
-        Self(Mutex::new(AppConfig::default().sanitize()))
+        Self {
+            config: Mutex::new(AppConfig::default()),
+            warnings: Mutex::new(Vec::new()),
+            subscriptions: Mutex::new(Vec::new()),
+            session_off: Mutex::new(Vec::new()),
+        }
     }
 
     pub fn get(&self) -> AppConfig {
-        self.0.lock().unwrap().clone()
+        self.config.lock().unwrap().clone()
+    }
+
+    /// The config as the settings file holds it: switches that are only off
+    /// for this session keep their stored value.
+    pub fn saved(&self) -> AppConfig {
+        self.disk_view(self.get())
     }
 
     pub fn set(&self, cfg: AppConfig) -> AppConfig {
-        let cfg = cfg.sanitize();
-        *self.0.lock().unwrap() = cfg.clone();
+        self.replace(cfg.clone());
+        cfg
+    }
+
+    /// Swaps in a new config and calls every listener subscribed to one of
+    /// the changed keys. Listeners run on the caller's thread, after the
+    /// store is updated, so they may read it. Delivery is not serialized:
+    /// when two threads replace the config at once, a listener may see
+    /// their changes in either order, so it should act on the config it is
+    /// given rather than on the order of changes.
+    pub fn replace(&self, cfg: AppConfig) -> SettingsChange {
+        let old = std::mem::replace(&mut *self.config.lock().unwrap(), cfg.clone());
+        let change = SettingsChange::between(&old, &cfg);
+        if change.is_empty() {
+            return change;
+        }
+
+        let listeners = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|sub| sub.keys.is_empty() || change.touches(sub.keys))
+            .map(|sub| sub.listener.clone())
+            .collect::<Vec<_>>();
+        for listener in listeners {
+            listener(&change, &cfg);
+        }
+        change
+    }
+
+    /// Registers a listener for changes to any of `keys`; an empty slice
+    /// subscribes to every change.
+    pub fn subscribe<F>(&self, keys: &'static [&'static str], listener: F)
+    where
+        F: Fn(&SettingsChange, &AppConfig) + Send + Sync + 'static,
+    {
+        self.subscriptions.lock().unwrap().push(Subscription {
+            keys,
+            listener: Arc::new(listener),
+        });
+    }
+
+    /// Fields that fell back to defaults during the last load.
+    pub fn warnings(&self) -> Vec<LoadWarning> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    fn set_warnings(&self, warnings: Vec<LoadWarning>) {
+        *self.warnings.lock().unwrap() = warnings;
+    }
+
+    /// Turns injection switches off until the app exits without touching the
+    /// settings file. Takes effect on the next load.
+    pub fn disable_for_session(&self, keys: &[&'static str]) {
+        let mut session_off = self.session_off.lock().unwrap();
+        for &key in keys {
+            if !session_off.iter().any(|(off, _)| *off == key) {
+                session_off.push((key, false));
+            }
+        }
+    }
+
+    /// Switches off for this run only that are on in the settings file.
+    pub fn held_back(&self) -> Vec<&'static str> {
+        self.session_off
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, on_disk)| *on_disk)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    pub fn is_off_for_session(&self, key: &str) -> bool {
+        self.session_off
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(off, _)| *off == key)
+    }
+
+    /// The config the app runs with for settings read from disk.
+    fn session_view(&self, mut cfg: AppConfig) -> AppConfig {
+        for (key, on_disk) in self.session_off.lock().unwrap().iter_mut() {
+            if let Some(value) = cfg.injection_mut(key) {
+                *on_disk = std::mem::take(value);
+            }
+        }
         cfg
     }
+
+    /// The config to write for a session one: switches left off keep their
+    /// stored value.
+    fn disk_view(&self, mut cfg: AppConfig) -> AppConfig {
+        for &(key, on_disk) in self.session_off.lock().unwrap().iter() {
+            if let Some(value) = cfg.injection_mut(key).filter(|value| !**value) {
+                *value = on_disk;
+            }
+        }
+        cfg
+    }
+
+    /// Ends the override of every switch `cfg` turns back on. Only called
+    /// once `cfg` is saved, so a failed write keeps them.
+    fn end_overrides(&self, cfg: &AppConfig) {
+        let mut cfg = cfg.clone();
+        self.session_off
+            .lock()
+            .unwrap()
+            .retain(|&(key, _)| cfg.injection_mut(key).is_some_and(|value| !*value));
+    }
 }
 
-fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?;
+/// Must be called before anything resolves a data path.
+pub fn set_data_dir_override(dir: PathBuf) {
+    let _ = DATA_DIR_OVERRIDE.set(dir);
+}
+
+pub fn data_dir_override() -> Option<&'static Path> {
+    DATA_DIR_OVERRIDE.get().map(PathBuf::as_path)
+}
+
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = match data_dir_override() {
+        Some(dir) => dir.to_path_buf(),
+        None => app.path().app_data_dir().map_err(|e| e.to_string())?,
+    };
 
     fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
     Ok(dir)
 }
 
+/// `data_dir` without an app handle, resolved the way Tauri does it.
+pub fn headless_data_dir() -> Result<PathBuf, String> {
+    let dir = match data_dir_override() {
+        Some(dir) => dir.to_path_buf(),
+        None => dirs::data_dir()
+            .ok_or("failed to resolve the user data dir")?
+            .join(APP_IDENTIFIER),
+    };
+
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Settings file of the active profile.
 pub fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
-    data_dir(app).map(|dir| dir.join("settings.json"))
+    profile_path(app, &active_profile(app))
+}
+
+fn profile_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    profile_path_in(&data_dir(app)?, name)
+}
+
+/// The default profile lives in `settings.json`; named ones in
+/// `profiles/<name>.json`.
+fn profile_path_in(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    if name == DEFAULT_PROFILE {
+        return Ok(dir.join("settings.json"));
+    }
+    let dir = dir.join("profiles");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("{name}.json")))
 }
 
 pub fn state_path(app: &AppHandle) -> Result<PathBuf, String> {
     data_dir(app).map(|dir| dir.join("state.json"))
 }
 
+/// Only the platform config dir has one; an explicit data dir starts clean.
 fn legacy_config_path(app: &AppHandle) -> Option<PathBuf> {
+    if data_dir_override().is_some() {
+        return None;
+    }
     app.path().app_config_dir().ok().map(|dir| dir.join("config.json"))
 }
 
-fn read_settings(path: &PathBuf) -> Result<AppConfig, String> {
-    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str::<AppConfig>(&contents).map_err(|e| e.to_string())
+/// Values kept out of a settings file, in an owner-only file beside it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Secrets {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vencord_auth_token: Option<String>,
+}
+
+/// `settings.json` keeps its secrets in `settings.secrets.json`, a profile
+/// `<name>.json` in `<name>.secrets.json`.
+fn secrets_path(settings_path: &Path) -> PathBuf {
+    let mut name = settings_path.file_stem().unwrap_or_default().to_os_string();
+    name.push(".secrets.json");
+    settings_path.with_file_name(name)
+}
+
+fn read_secrets(settings_path: &Path) -> Secrets {
+    let path = secrets_path(settings_path);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => return Secrets::default(),
+    };
+    serde_json::from_slice(&bytes).unwrap_or_else(|err| {
+        log::warn!("Ignoring unreadable {}: {err}", path.display());
+        Secrets::default()
+    })
+}
+
+/// Stores the token of `cfg`. Without one the file is left alone, since
+/// configs coming back from the page never carry it; an empty one removes
+/// it.
+fn write_secrets(settings_path: &Path, cfg: &AppConfig) -> Result<(), String> {
+    let path = secrets_path(settings_path);
+    match cfg.vencord_source.auth_token.as_deref() {
+        None => Ok(()),
+        Some("") if path.exists() => fs::remove_file(&path).map_err(|e| e.to_string()),
+        Some("") => Ok(()),
+        Some(token) => {
+            let secrets = Secrets {
+                vencord_auth_token: Some(token.to_string()),
+            };
+            let json = serde_json::to_string_pretty(&secrets).map_err(|e| e.to_string())?;
+            persist::write_private(&path, json.as_bytes())
+        }
+    }
+}
+
+/// Reads a settings file of any schema version. Only an unreadable file or
+/// invalid JSON is an error; bad fields come back as warnings.
+fn read_settings(path: &Path) -> Result<LoadedConfig, String> {
+    let value = persist::read_json::<serde_json::Value>(path)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut loaded = AppConfig::from_versioned(value);
+    let source = &mut loaded.config.vencord_source;
+    if source.auth_token.is_some() {
+        // Older builds kept the token in the settings file; writing it back
+        // moves it to the secrets file.
+        loaded.migrated = true;
+    } else {
+        source.auth_token = read_secrets(path).vencord_auth_token;
+    }
+    Ok(loaded)
 }
 
-fn write_settings(path: &PathBuf, cfg: &AppConfig) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(cfg).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())
+fn write_settings(path: &Path, cfg: &AppConfig) -> Result<(), String> {
+    write_secrets(path, cfg)?;
+    persist::write_json(path, &cfg.to_versioned()?)
+}
+
+/// Settings file of `profile` in `dir`, or of the profile the app last ran
+/// with.
+pub fn headless_settings_path(dir: &Path, profile: Option<&str>) -> Result<PathBuf, String> {
+    let name = match profile {
+        Some(name) => {
+            validate_profile_name(name)?;
+            name.to_string()
+        }
+        None => state::read_state(&dir.join("state.json"))
+            .ok()
+            .and_then(|(state, _)| state.active_profile)
+            .filter(|name| validate_profile_name(name).is_ok())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+    };
+    let path = profile_path_in(dir, &name)?;
+    if name != DEFAULT_PROFILE && !persist::exists(&path) {
+        return Err(format!("profile \"{name}\" does not exist"));
+    }
+    Ok(path)
+}
+
+/// Reads a settings file the way the app would, defaults included.
+pub fn read_settings_file(path: &Path) -> Result<AppConfig, String> {
+    if persist::exists(path) {
+        read_settings(path).map(|loaded| loaded.config)
+    } else {
+        Ok(AppConfig::default())
+    }
+}
+
+/// Validates and writes `cfg` for a headless command.
+pub fn write_settings_file(path: &Path, cfg: &AppConfig) -> Result<(), String> {
+    check_valid(&read_settings_file(path)?, cfg)?;
+    write_settings(path, cfg)
+}
+
+/// Reads another profile's settings without making it active.
+pub fn read_profile(app: &AppHandle, name: &str) -> Result<AppConfig, String> {
+    validate_profile_name(name)?;
+    let path = profile_path(app, name)?;
+    if persist::exists(&path) {
+        read_settings(&path).map(|loaded| loaded.config)
+    } else {
+        Ok(AppConfig::default())
+    }
 }
 
 pub fn load_settings(app: &AppHandle, store: &SettingsStore) -> Result<AppConfig, String> {
     let path = settings_path(app)?;
+    let is_default = active_profile(app) == DEFAULT_PROFILE;
 
-    let cfg = if path.exists() {
+    let loaded = if persist::exists(&path) {
         read_settings(&path)?
-    } else if let Some(legacy_path) = legacy_config_path(app) {
+    } else if let Some(legacy_path) = legacy_config_path(app).filter(|_| is_default) {
         if legacy_path.exists() {
             read_settings(&legacy_path)?
         } else {
-            AppConfig::default()
+            AppConfig::default().into()
         }
     } else {
-        AppConfig::default()
+        AppConfig::default().into()
     };
 
-    let cfg = store.set(cfg);
-    if !path.exists() {
-        let _ = write_settings(&path, &cfg);
+    let mut config = loaded.config;
+    let adopted = themes::adopt_pending_css(app, &mut config);
+    if loaded.migrated || adopted || !persist::exists(&path) {
+        if let Err(err) = write_settings(&path, &config) {
+            log::warn!("Failed to write settings {}: {err}", path.display());
+        }
     }
+    let cfg = store.set(store.session_view(config));
+    store.set_warnings(loaded.warnings);
 
     Ok(cfg)
 }
 
+/// Rejects `cfg` for problems `previous` does not already have. One that
+/// was there before, like a theme file deleted on disk, is only logged, so
+/// it does not block saving unrelated changes. Errors are matched on their
+/// top-level key and message, so reordering a list does not make an old
+/// one new.
+fn check_valid(previous: &AppConfig, cfg: &AppConfig) -> Result<(), String> {
+    let key = |error: &FieldError| {
+        let top = error.field.split('.').next().unwrap_or_default().to_string();
+        (top, error.message.clone())
+    };
+    let known = previous.validate().iter().map(key).collect::<Vec<_>>();
+
+    let mut rejected = Vec::new();
+    for error in cfg.validate() {
+        if known.contains(&key(&error)) {
+            log::warn!("Saving with existing problem in {}: {}", error.field, error.message);
+        } else {
+            rejected.push(format!("{}: {}", error.field, error.message));
+        }
+    }
+    if rejected.is_empty() {
+        Ok(())
+    } else {
+        Err(rejected.join("; "))
+    }
+}
+
 pub fn save_settings(
     app: &AppHandle,
     store: &SettingsStore,
-    cfg: AppConfig,
+    mut cfg: AppConfig,
 ) -> Result<AppConfig, String> {
-    let cfg = store.set(cfg);
+    if cfg.vencord_source.auth_token.is_none() {
+        cfg.vencord_source.auth_token = store.get().vencord_source.auth_token;
+    }
+    check_valid(&store.get(), &cfg)?;
+
     let path = settings_path(app)?;
-    write_settings(&path, &cfg)?;
-    emit_settings_changed(app, &cfg);
+    write_settings(&path, &store.disk_view(cfg.clone()))?;
+    store.end_overrides(&cfg);
+    store.set_warnings(Vec::new());
+    let change = store.replace(cfg.clone());
+    emit_settings_changed(app, &change);
     Ok(cfg)
 }
 
-pub fn emit_settings_changed(app: &AppHandle, cfg: &AppConfig) {
-    if let Err(err) = app.emit(SETTINGS_CHANGED_EVENT, cfg) {
+pub fn emit_settings_changed(app: &AppHandle, change: &SettingsChange) {
+    if change.is_empty() {
+        return;
+    }
+    if let Err(err) = app.emit(SETTINGS_CHANGED_EVENT, change) {
         log::warn!("Failed to emit settings change event: {err}");
     }
 }
 
+pub fn active_profile(app: &AppHandle) -> String {
+    app.state::<state::StateStore>()
+        .get()
+        .active_profile
+        .filter(|name| validate_profile_name(name).is_ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        && name.trim() == name;
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid profile name \"{name}\": use letters, digits, spaces, - or _"
+        ))
+    }
+}
+
+pub fn list_profiles(app: &AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    let active = active_profile(app);
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+
+    let dir = data_dir(app)?.join("profiles");
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                if name != DEFAULT_PROFILE && validate_profile_name(name).is_ok() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names[1..].sort();
+
+    Ok(names
+        .into_iter()
+        .map(|name| ProfileInfo {
+            active: name == active,
+            name,
+        })
+        .collect())
+}
+
+/// Creates a profile from defaults, or as a copy of `from` when given.
+pub fn create_profile(app: &AppHandle, name: &str, from: Option<&str>) -> Result<(), String> {
+    validate_profile_name(name)?;
+    let path = profile_path(app, name)?;
+    if persist::exists(&path) {
+        return Err(format!("profile \"{name}\" already exists"));
+    }
+
+    match from {
+        Some(from) => {
+            validate_profile_name(from)?;
+            let source = profile_path(app, from)?;
+            if persist::exists(&source) {
+                copy_settings(&source, &path)
+            } else if from == DEFAULT_PROFILE {
+                write_settings(&path, &AppConfig::default())
+            } else {
+                Err(format!("profile \"{from}\" does not exist"))
+            }
+        }
+        None => write_settings(&path, &AppConfig::default()),
+    }
+}
+
+/// Copies a settings file to `target` together with its secrets.
+fn copy_settings(source: &Path, target: &Path) -> Result<(), String> {
+    write_settings(target, &read_settings(source)?.config)
+}
+
+pub fn delete_profile(app: &AppHandle, name: &str) -> Result<(), String> {
+    validate_profile_name(name)?;
+    if name == DEFAULT_PROFILE {
+        return Err("the default profile cannot be deleted".to_string());
+    }
+    if name == active_profile(app) {
+        return Err("switch to another profile before deleting this one".to_string());
+    }
+
+    let path = profile_path(app, name)?;
+    if !persist::exists(&path) {
+        return Err(format!("profile \"{name}\" does not exist"));
+    }
+    for file in [path.clone(), persist::backup_path(&path), secrets_path(&path)] {
+        if file.exists() {
+            fs::remove_file(&file).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Makes `name` the active profile and loads it into the store, notifying
+/// settings listeners of everything that differs from the previous profile.
+pub fn switch_profile(
+    app: &AppHandle,
+    store: &SettingsStore,
+    name: &str,
+) -> Result<AppConfig, String> {
+    validate_profile_name(name)?;
+    if name != DEFAULT_PROFILE && !persist::exists(&profile_path(app, name)?) {
+        return Err(format!("profile \"{name}\" does not exist"));
+    }
+
+    let state_store = app.state::<state::StateStore>();
+    let mut app_state = state_store.get();
+    app_state.active_profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    state::save_state(app, &state_store, app_state)?;
+
+    let previous = store.get();
+    let cfg = load_settings(app, store)?;
+    emit_settings_changed(app, &SettingsChange::between(&previous, &cfg));
+    log::info!("Switched to settings profile {name}");
+    Ok(cfg)
+}
+
+/// Overwrites a profile's settings file and makes it the active profile.
+/// Meant for startup, before the settings are loaded.
+pub fn restore_profile(app: &AppHandle, name: &str, cfg: &AppConfig) -> Result<(), String> {
+    validate_profile_name(name)?;
+    write_settings(&profile_path(app, name)?, cfg)?;
+
+    let state_store = app.state::<state::StateStore>();
+    let mut app_state = state_store.get();
+    app_state.active_profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    state::save_state(app, &state_store, app_state)
+}
+
 pub fn log_paths(app: &AppHandle) {
     match settings_path(app) {
         Ok(path) => log::info!("Settings path: {}", path.display()),
@@ -107,3 +639,153 @@ pub fn log_paths(app: &AppHandle) {
         Err(err) => log::warn!("Failed to resolve state path: {err}"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_switches_keep_the_stored_values() {
+        let store = SettingsStore::new();
+        store.disable_for_session(&["enable_vencord", "enable_theme", "enable_blockers"]);
+
+        let on_disk = AppConfig {
+            enable_vencord: true,
+            enable_theme: false,
+            enable_blockers: true,
+            ..AppConfig::default()
+        };
+        let session = store.session_view(on_disk);
+        assert!(!session.enable_vencord && !session.enable_theme && !session.enable_blockers);
+        assert_eq!(store.held_back(), ["enable_vencord", "enable_blockers"]);
+        // Exports and bundle previews go by the stored values.
+        store.set(session.clone());
+        let saved = store.saved();
+        assert!(saved.enable_vencord && !saved.enable_theme && saved.enable_blockers);
+
+        // Saving with the switches still off writes what was stored; turning
+        // one back on ends its override.
+        let mut edited = session.clone();
+        edited.enable_blockers = true;
+        edited.theme_hot_reload = true;
+        let written = store.disk_view(edited.clone());
+        assert!(written.enable_vencord && !written.enable_theme && written.enable_blockers);
+        assert!(written.theme_hot_reload);
+        // Until the write succeeds, the override stays.
+        assert!(store.is_off_for_session("enable_blockers"));
+        store.end_overrides(&edited);
+        assert!(!store.is_off_for_session("enable_blockers"));
+        assert_eq!(store.held_back(), ["enable_vencord"]);
+    }
+
+    #[test]
+    fn existing_problems_do_not_block_other_changes() {
+        use crate::config::ThemeEntry;
+
+        let theme = |file: &str| ThemeEntry {
+            file: file.to_string(),
+            enabled: true,
+            url: None,
+        };
+        let previous = AppConfig {
+            themes: vec![theme("/nonexistent/gone.theme.css"), theme("kept.theme.css")],
+            ..AppConfig::default()
+        };
+
+        let mut toggled = previous.clone();
+        toggled.enable_blockers = !toggled.enable_blockers;
+        toggled.themes.reverse();
+        assert!(check_valid(&previous, &toggled).is_ok());
+
+        let mut broken = toggled.clone();
+        broken.theme_max_bytes = 10;
+        let err = check_valid(&previous, &broken).unwrap_err();
+        assert!(err.starts_with("theme_max_bytes:"), "{err}");
+        assert!(!err.contains("gone.theme.css"), "{err}");
+    }
+
+    #[test]
+    fn changes_list_top_level_keys_with_both_values() {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        new.enable_theme = true;
+        new.vencord_source.base_url = Some("https://mirror.example".to_string());
+
+        let change = SettingsChange::between(&old, &new);
+        let keys: Vec<&str> = change.changed.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, ["enable_theme", "vencord_source"]);
+
+        let source = &change.changed[1];
+        assert_eq!(source.old["base_url"], Value::Null);
+        assert_eq!(source.new["base_url"], "https://mirror.example");
+        assert!(change.touches(&["vencord_pin", "vencord_source"]));
+        assert!(!change.touches(&["vencord_pin"]));
+        assert!(SettingsChange::between(&old, &old.clone()).is_empty());
+    }
+
+    #[test]
+    fn listeners_only_hear_their_keys() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let store = SettingsStore::new();
+        let counter = |keys: &'static [&'static str]| {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let seen = calls.clone();
+            store.subscribe(keys, move |_, _| {
+                seen.fetch_add(1, Ordering::SeqCst);
+            });
+            calls
+        };
+        let theme = counter(&["enable_theme", "themes"]);
+        let everything = counter(&[]);
+        let pin = counter(&["vencord_pin"]);
+
+        let mut cfg = store.get();
+        cfg.enable_theme = true;
+        store.replace(cfg.clone());
+        // Nothing changed, so nobody is called.
+        store.replace(cfg);
+
+        assert_eq!(theme.load(Ordering::SeqCst), 1);
+        assert_eq!(everything.load(Ordering::SeqCst), 1);
+        assert_eq!(pin.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn copied_profiles_keep_their_token() {
+        let dir = std::env::temp_dir().join(format!("ghostcord-profiles-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("work.json");
+        let target = dir.join("copy.json");
+
+        let mut cfg = AppConfig {
+            enable_theme: true,
+            ..AppConfig::default()
+        };
+        cfg.vencord_source.auth_token = Some("secret".to_string());
+        write_settings(&source, &cfg).unwrap();
+        copy_settings(&source, &target).unwrap();
+
+        let copied = read_settings(&target).unwrap().config;
+        assert!(copied.enable_theme);
+        assert_eq!(copied.vencord_source.auth_token.as_deref(), Some("secret"));
+        assert!(secrets_path(&target).exists());
+        // The token stays out of the settings file itself.
+        let raw = fs::read_to_string(&target).unwrap();
+        assert!(!raw.contains("secret"), "{raw}");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn profile_names_stay_plain_file_names() {
+        for name in ["default", "work", "Gaming 2", "low-mem_x"] {
+            assert!(validate_profile_name(name).is_ok(), "{name}");
+        }
+        let too_long = "a".repeat(65);
+        for name in ["", " work", "work ", "../x", "a/b", "a.b", "ä", too_long.as_str()] {
+            assert!(validate_profile_name(name).is_err(), "{name:?}");
+        }
+    }
+}
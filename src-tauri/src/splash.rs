@@ -0,0 +1,116 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::{protocols, settings, tray};
+
+pub const SPLASH_LABEL: &str = "splash";
+pub const SPLASH_STATUS_EVENT: &str = "ghostcord://splash-status";
+
+/// The main window is shown anyway after this long, so a stalled download
+/// or page never leaves only the splash on screen.
+const SPLASH_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// What has to happen before the splash gives way to the main window.
+#[derive(Default)]
+pub struct SplashGate {
+    page_loaded: AtomicBool,
+    assets_ready: AtomicBool,
+    finished: AtomicBool,
+    start_minimized: AtomicBool,
+}
+
+/// Opens the splash window. The main window starts hidden and is shown by
+/// `finish`, whether or not the splash could be created.
+pub fn open(app: &AppHandle) {
+    let url = match protocols::bundled_url("splash.html") {
+        Ok(url) => url,
+        Err(err) => {
+            log::warn!("Splash page unavailable: {err}");
+            return;
+        }
+    };
+    let mut builder = WebviewWindowBuilder::new(app, SPLASH_LABEL, WebviewUrl::CustomProtocol(url));
+    if let Some(dir) = settings::data_dir_override() {
+        builder = builder.data_directory(dir.join("webview"));
+    }
+    let built = builder
+        .title("Ghostcord Lite")
+        .inner_size(320.0, 200.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .center()
+        .build();
+    if let Err(err) = built {
+        log::warn!("Failed to open splash window: {err}");
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SPLASH_TIMEOUT).await;
+        if !app.state::<SplashGate>().finished.load(Ordering::SeqCst) {
+            log::warn!("Startup is taking long, showing the main window");
+            finish(&app);
+        }
+    });
+}
+
+pub fn status(app: &AppHandle, message: &str) {
+    if let Err(err) = app.emit_to(SPLASH_LABEL, SPLASH_STATUS_EVENT, message) {
+        log::warn!("Failed to emit splash status: {err}");
+    }
+}
+
+/// Leaves the main window in the tray (or minimized without one) once
+/// loading is done. The splash goes right away since nothing is shown.
+pub fn start_minimized(app: &AppHandle) {
+    app.state::<SplashGate>()
+        .start_minimized
+        .store(true, Ordering::SeqCst);
+    if let Some(splash) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = splash.close();
+    }
+}
+
+/// Called once the main webview has finished loading.
+pub fn page_loaded(app: &AppHandle) {
+    let gate = app.state::<SplashGate>();
+    gate.page_loaded.store(true, Ordering::SeqCst);
+    if gate.assets_ready.load(Ordering::SeqCst) {
+        finish(app);
+    } else {
+        status(app, "Preparing Vencord…");
+    }
+}
+
+/// Called once Vencord is ready to inject, or is not going to be.
+pub fn assets_ready(app: &AppHandle) {
+    let gate = app.state::<SplashGate>();
+    gate.assets_ready.store(true, Ordering::SeqCst);
+    if gate.page_loaded.load(Ordering::SeqCst) {
+        finish(app);
+    }
+}
+
+fn finish(app: &AppHandle) {
+    let gate = app.state::<SplashGate>();
+    if gate.finished.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(main) = app.get_webview_window("main") {
+        if !gate.start_minimized.load(Ordering::SeqCst) {
+            let _ = main.show();
+            let _ = main.set_focus();
+        } else if !tray::is_available(app) {
+            let _ = main.show();
+            let _ = main.minimize();
+        }
+    }
+    if let Some(splash) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = splash.close();
+    }
+}
@@ -1,9 +1,16 @@
-use std::{fs, path::PathBuf, sync::Mutex};
+use std::{path::Path, sync::Mutex};
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use tauri::AppHandle;
 
-use crate::settings;
+use crate::{
+    migrate::{self, Migration},
+    persist, settings,
+};
+
+/// Upgrade steps for `state.json`; the index is the version migrated from.
+pub const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct WindowBounds {
@@ -18,6 +25,23 @@ pub struct AppState {
     pub window_bounds: Option<WindowBounds>,
     pub last_active_channel: Option<String>,
     pub updater_snooze_until: Option<String>,
+    /// Settings profile to load; `None` means the default profile.
+    pub active_profile: Option<String>,
+    /// Launches so far, counting the current one.
+    #[serde(default)]
+    pub launch_count: u64,
+    /// `launch_count` of the last launch that finished starting up, or
+    /// exited cleanly after loading. Only the former proves the settings
+    /// work; either means the launch did not crash on the way up.
+    #[serde(default)]
+    pub last_finished_launch: u64,
+    /// Vencord release a rollback pinned, until the user keeps or lifts the
+    /// pin.
+    pub recovery_pin: Option<String>,
+    /// Keys this build does not know about, kept so that saving after a
+    /// downgrade does not wipe state written by a newer version.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 pub struct StateStore(pub Mutex<AppState>);
@@ -37,27 +61,70 @@ impl StateStore {
     }
 }
 
-fn read_state(path: &PathBuf) -> Result<AppState, String> {
-    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str::<AppState>(&contents).map_err(|e| e.to_string())
+impl AppState {
+    /// Migrates a raw state document to the latest schema and deserializes
+    /// it. The flag reports whether a migration ran.
+    pub fn from_versioned(mut value: Value) -> Result<(Self, bool), String> {
+        let migrated = migrate::run(&mut value, MIGRATIONS)?;
+        if let Some(object) = value.as_object_mut() {
+            migrate::strip_version(object, MIGRATIONS);
+        }
+        let state = serde_json::from_value::<Self>(value).map_err(|e| e.to_string())?;
+        Ok((state, migrated))
+    }
+
+    /// Launches in a row, up to the last one, that never finished starting.
+    pub fn failed_launches(&self) -> u64 {
+        self.launch_count.saturating_sub(self.last_finished_launch)
+    }
+
+    pub fn to_versioned(&self) -> Result<Value, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        migrate::stamp(&mut value, MIGRATIONS);
+        Ok(value)
+    }
 }
 
-fn write_state(path: &PathBuf, state: &AppState) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())
+/// v0 is the unversioned `state.json`. A window bounds object that does not
+/// fit `WindowBounds` (e.g. negative sizes) is dropped rather than failing
+/// the whole file, and non-string text fields are cleared.
+fn migrate_v0_to_v1(state: &mut Map<String, Value>) -> Result<(), String> {
+    let bounds_valid = match state.get("window_bounds") {
+        None | Some(Value::Null) => true,
+        Some(bounds) => serde_json::from_value::<WindowBounds>(bounds.clone()).is_ok(),
+    };
+    if !bounds_valid {
+        state.insert("window_bounds".to_string(), Value::Null);
+    }
+
+    for key in ["last_active_channel", "updater_snooze_until"] {
+        if state.get(key).is_some_and(|value| !value.is_string()) {
+            state.insert(key.to_string(), Value::Null);
+        }
+    }
+    Ok(())
+}
+
+pub fn read_state(path: &Path) -> Result<(AppState, bool), String> {
+    let value = persist::read_json::<Value>(path)?;
+    AppState::from_versioned(value).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn write_state(path: &Path, state: &AppState) -> Result<(), String> {
+    persist::write_json(path, &state.to_versioned()?)
 }
 
 pub fn load_state(app: &AppHandle, store: &StateStore) -> Result<AppState, String> {
     let path = settings::state_path(app)?;
 
-    let state = if path.exists() {
+    let (state, migrated) = if persist::exists(&path) {
         read_state(&path)?
     } else {
-        AppState::default()
+        (AppState::default(), false)
     };
 
     let state = store.set(state);
-    if !path.exists() {
+    if migrated || !persist::exists(&path) {
         let _ = write_state(&path, &state);
     }
 
@@ -73,3 +140,81 @@ pub fn save_state(
     let path = settings::state_path(app)?;
     write_state(&path, &state)
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn v0_to_v1_keeps_valid_bounds() {
+        let bounds = json!({ "x": -20, "y": 10, "width": 1100, "height": 780 });
+        let mut state = object(json!({ "window_bounds": bounds.clone() }));
+        migrate_v0_to_v1(&mut state).unwrap();
+        assert_eq!(state["window_bounds"], bounds);
+    }
+
+    #[test]
+    fn v0_to_v1_drops_invalid_bounds() {
+        let mut state = object(json!({
+            "window_bounds": { "x": 0, "y": 0, "width": -1, "height": 780 }
+        }));
+        migrate_v0_to_v1(&mut state).unwrap();
+        assert_eq!(state["window_bounds"], Value::Null);
+    }
+
+    #[test]
+    fn v0_to_v1_clears_non_string_fields() {
+        let mut state = object(json!({
+            "last_active_channel": 1234,
+            "updater_snooze_until": "2026-01-01",
+        }));
+        migrate_v0_to_v1(&mut state).unwrap();
+        assert_eq!(state["last_active_channel"], Value::Null);
+        assert_eq!(state["updater_snooze_until"], json!("2026-01-01"));
+    }
+
+    #[test]
+    fn legacy_document_migrates_to_latest() {
+        let legacy = json!({ "window_bounds": "broken", "last_active_channel": "123" });
+        let (state, migrated) = AppState::from_versioned(legacy).unwrap();
+        assert!(migrated);
+        assert!(state.window_bounds.is_none());
+        assert_eq!(state.last_active_channel.as_deref(), Some("123"));
+    }
+
+    #[test]
+    fn counts_failed_launches_since_the_last_finished_one() {
+        let (state, _) = AppState::from_versioned(json!({ "schema_version": 1 })).unwrap();
+        assert_eq!(state.failed_launches(), 0);
+
+        let state = AppState {
+            launch_count: 7,
+            last_finished_launch: 4,
+            ..AppState::default()
+        };
+        assert_eq!(state.failed_launches(), 3);
+    }
+
+    #[test]
+    fn newer_state_keeps_its_version_and_fields() {
+        let newer = MIGRATIONS.len() as u64 + 1;
+        let value = json!({ "schema_version": newer, "future_field": [1] });
+        let (state, _) = AppState::from_versioned(value).unwrap();
+        let saved = state.to_versioned().unwrap();
+        assert_eq!(saved["schema_version"], json!(newer));
+        assert_eq!(saved["future_field"], json!([1]));
+    }
+
+    #[test]
+    fn versioned_round_trip_skips_migrations() {
+        let value = AppState::default().to_versioned().unwrap();
+        let (_, migrated) = AppState::from_versioned(value).unwrap();
+        assert!(!migrated);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::persist;
+
+const INDEX_FILE: &str = "index.json";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Validators for one cached URL, used to revalidate instead of
+/// downloading again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheRecord {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: u64,
+}
+
+/// `index.json` of a cache dir, keyed by cache file name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    entries: BTreeMap<String, CacheRecord>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+    Updated,
+    NotModified,
+    /// The server could not be reached or refused; the cached copy stays.
+    Offline(String),
+}
+
+fn cache_file_name(url: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(url.as_bytes()));
+    format!("{}.css", &digest[..16])
+}
+
+pub fn cached_path(dir: &Path, url: &str) -> PathBuf {
+    dir.join(cache_file_name(url))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn header(response: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// Reads the body in chunks, giving up as soon as it grows past `max_bytes`
+/// so an oversized download is never held in memory whole.
+async fn read_body(mut response: reqwest::Response, max_bytes: u64) -> Result<Vec<u8>, String> {
+    let too_large = || format!("download is over the {} KiB limit", max_bytes / 1024);
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if (body.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Downloads `url` into `dir`, revalidating an existing copy with
+/// `If-None-Match`/`If-Modified-Since`. Bodies over `max_bytes` are refused.
+/// Only fails when there is no cached copy to fall back to.
+pub async fn fetch(
+    client: &reqwest::Client,
+    dir: &Path,
+    url: &str,
+    max_bytes: u64,
+) -> Result<Fetched, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let index_path = dir.join(INDEX_FILE);
+    let mut index = if persist::exists(&index_path) {
+        persist::read_json::<CacheIndex>(&index_path).unwrap_or_default()
+    } else {
+        CacheIndex::default()
+    };
+
+    let name = cache_file_name(url);
+    let path = dir.join(&name);
+    let record = index
+        .entries
+        .get(&name)
+        .filter(|record| record.url == url && path.is_file())
+        .cloned();
+
+    let mut request = client.get(url).timeout(FETCH_TIMEOUT);
+    if let Some(record) = &record {
+        if let Some(etag) = &record.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &record.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(err) if record.is_some() => return Ok(Fetched::Offline(err.to_string())),
+        Err(err) => return Err(format!("{url}: {err}")),
+    };
+    let status = response.status();
+
+    if status == StatusCode::NOT_MODIFIED {
+        if let Some(mut record) = record {
+            record.fetched_at = now_secs();
+            index.entries.insert(name, record);
+            persist::write_json(&index_path, &index)?;
+            return Ok(Fetched::NotModified);
+        }
+    }
+    if !status.is_success() {
+        return match record {
+            Some(_) => Ok(Fetched::Offline(format!("{url}: {status}"))),
+            None => Err(format!("{url}: {status}")),
+        };
+    }
+
+    let etag = header(&response, ETAG);
+    let last_modified = header(&response, LAST_MODIFIED);
+    let bytes = match read_body(response, max_bytes).await {
+        Ok(bytes) => bytes,
+        Err(err) if record.is_some() => return Ok(Fetched::Offline(format!("{url}: {err}"))),
+        Err(err) => return Err(format!("{url}: {err}")),
+    };
+    persist::write_atomic(&path, &bytes)?;
+    index.entries.insert(
+        name,
+        CacheRecord {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            fetched_at: now_secs(),
+        },
+    );
+    persist::write_json(&index_path, &index)?;
+    Ok(Fetched::Updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+
+    /// Serves one canned response per connection and hands back the
+    /// request headers it saw.
+    fn serve(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/theme.css", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut seen = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut headers = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    headers.push_str(&line.to_ascii_lowercase());
+                }
+                seen.push(headers);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            seen
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn revalidates_with_etag_and_keeps_copy_offline() {
+        let dir =
+            std::env::temp_dir().join(format!("ghostcord-theme-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let client = reqwest::Client::new();
+
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 8\r\nConnection: close\r\n\r\na{b:c;}\n",
+            "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+        ]);
+        tauri::async_runtime::block_on(async {
+            assert_eq!(
+                fetch(&client, &dir, &url, 1024).await.unwrap(),
+                Fetched::Updated
+            );
+            assert_eq!(
+                fetch(&client, &dir, &url, 1024).await.unwrap(),
+                Fetched::NotModified
+            );
+        });
+        let seen = server.join().unwrap();
+        assert!(!seen[0].contains("if-none-match"));
+        assert!(seen[1].contains("if-none-match: \"v1\""));
+
+        // The stand-in is gone now, so this is the offline case.
+        let fetched = tauri::async_runtime::block_on(fetch(&client, &dir, &url, 1024)).unwrap();
+        assert!(matches!(fetched, Fetched::Offline(_)));
+        assert_eq!(
+            std::fs::read_to_string(cached_path(&dir, &url)).unwrap(),
+            "a{b:c;}\n"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn oversized_downloads_are_refused() {
+        let dir = std::env::temp_dir().join(format!("ghostcord-theme-cap-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let client = reqwest::Client::new();
+
+        // One declares its length, the other only shows it while streaming.
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 16\r\nConnection: close\r\n\r\na{b:c;}\na{b:c;}\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\na{b:c;}\na{b:c;}\n",
+            "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\na{b:c;}\n",
+        ]);
+        tauri::async_runtime::block_on(async {
+            let err = fetch(&client, &dir, &url, 10).await.unwrap_err();
+            assert!(err.contains("over the"), "{err}");
+            let err = fetch(&client, &dir, &url, 10).await.unwrap_err();
+            assert!(err.contains("over the"), "{err}");
+            assert!(!cached_path(&dir, &url).exists());
+            assert_eq!(
+                fetch(&client, &dir, &url, 10).await.unwrap(),
+                Fetched::Updated
+            );
+        });
+        server.join().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
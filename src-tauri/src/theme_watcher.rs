@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{commands, config::ThemeEntry, settings::SettingsStore, themes};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Editors often write a file in several steps; wait for it to settle.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+type Snapshot = HashMap<PathBuf, Option<(SystemTime, u64)>>;
+
+fn snapshot(files: &[PathBuf]) -> Snapshot {
+    files
+        .iter()
+        .map(|path| {
+            let stamp = fs::metadata(path)
+                .ok()
+                .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+            (path.clone(), stamp)
+        })
+        .collect()
+}
+
+/// Every file the given themes are built from, including unreadable
+/// theme files so they are picked up once they appear.
+fn theme_files(dir: &Path, watched: &[ThemeEntry]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for theme in watched {
+        let deps = themes::read_theme(dir, theme)
+            .map(|source| source.files)
+            .unwrap_or_else(|_| vec![themes::theme_path(dir, theme)]);
+        for dep in deps {
+            if !files.contains(&dep) {
+                files.push(dep);
+            }
+        }
+    }
+    files
+}
+
+/// Polls the enabled theme files and their local `@import`s and re-applies
+/// the config to the main window when any of them changes. Polling keeps
+/// this dependency free; the files involved are few and small.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    let spawned = thread::Builder::new()
+        .name("theme-watcher".to_string())
+        .spawn(move || {
+            let mut watched: Vec<ThemeEntry> = Vec::new();
+            let mut files: Vec<PathBuf> = Vec::new();
+            let mut last = Snapshot::new();
+            let mut changed_at: Option<Instant> = None;
+
+            loop {
+                thread::sleep(POLL_INTERVAL);
+
+                let cfg = app.state::<SettingsStore>().get();
+                let Ok(dir) = themes::themes_dir(&app) else {
+                    continue;
+                };
+                let enabled = cfg
+                    .themes
+                    .iter()
+                    .filter(|theme| cfg.theme_hot_reload && cfg.enable_theme && theme.enabled)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                if enabled.is_empty() {
+                    watched.clear();
+                    continue;
+                }
+                if watched != enabled {
+                    files = theme_files(&dir, &enabled);
+                    last = snapshot(&files);
+                    watched = enabled;
+                    changed_at = None;
+                    continue;
+                }
+
+                let current = snapshot(&files);
+                if current != last {
+                    last = current;
+                    changed_at = Some(Instant::now());
+                    continue;
+                }
+
+                if changed_at.is_some_and(|at| at.elapsed() >= DEBOUNCE) {
+                    changed_at = None;
+                    log::info!("Theme changed on disk, reloading");
+                    // An edit may have added or dropped an @import.
+                    files = theme_files(&dir, &watched);
+                    last = snapshot(&files);
+                    if let Err(err) = commands::apply_config_to_main(app.clone(), cfg) {
+                        log::warn!("Failed to reload theme: {err}");
+                    }
+                }
+            }
+        });
+
+    if let Err(err) = spawned {
+        log::warn!("Failed to start theme watcher: {err}");
+    }
+}
@@ -0,0 +1,1457 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    config::{AppConfig, ThemeEntry, PENDING_THEME_CSS_KEY},
+    commands, persist,
+    settings::{self, SettingsStore},
+    theme_cache::{self, Fetched},
+    vencord,
+};
+
+/// Nesting limit for `@import`s.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// Library file the settings panel's custom CSS box edits.
+pub const CUSTOM_CSS_FILE: &str = "custom.css";
+
+/// A theme with its `@import`s inlined, plus every local file that went
+/// into it so callers can watch them for changes.
+pub struct ThemeSource {
+    pub css: String,
+    pub files: Vec<PathBuf>,
+    /// Remote stylesheets it uses, cached or not.
+    pub remote: Vec<String>,
+}
+
+/// One enabled theme, ready to be injected as its own style element.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeStyle {
+    pub id: String,
+    pub file: String,
+    pub css: String,
+}
+
+pub fn themes_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = settings::data_dir(app)?.join("themes");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// The BetterDiscord-style `/** @name ... */` header of a theme file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ThemeMeta {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub source: Option<String>,
+}
+
+/// A theme file in the library or the active profile's list.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeInfo {
+    pub file: String,
+    pub path: String,
+    pub url: Option<String>,
+    /// Whether the active profile lists this theme; unlisted files are
+    /// library copies used by other profiles or left behind.
+    pub listed: bool,
+    pub enabled: bool,
+    pub meta: ThemeMeta,
+    /// Why the file cannot be used, if it cannot.
+    pub error: Option<String>,
+    /// Remote references stripped before injection.
+    pub blocked: Vec<String>,
+}
+
+/// A theme after the host allowlist has been applied.
+pub struct ScannedCss {
+    pub css: String,
+    pub blocked: Vec<String>,
+}
+
+/// Reads the metadata header at the top of a theme. Unknown tags are
+/// ignored; a file without a header gives empty metadata.
+pub fn parse_meta(css: &str) -> ThemeMeta {
+    let mut meta = ThemeMeta::default();
+    let css = css.trim_start_matches('\u{feff}').trim_start();
+    let Some(header) = css
+        .strip_prefix("/**")
+        .and_then(|rest| rest.find("*/").map(|end| &rest[..end]))
+    else {
+        return meta;
+    };
+
+    for line in header.lines() {
+        let line = line.trim().trim_start_matches('*').trim();
+        let Some(tag) = line.strip_prefix('@') else {
+            continue;
+        };
+        let (key, value) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        let slot = match key {
+            "name" => &mut meta.name,
+            "author" => &mut meta.author,
+            "version" => &mut meta.version,
+            "description" => &mut meta.description,
+            "source" => &mut meta.source,
+            _ => continue,
+        };
+        if slot.is_none() {
+            *slot = value;
+        }
+    }
+    meta
+}
+
+/// Cheap sanity check that a file is a stylesheet: not empty and with
+/// balanced braces outside comments and strings.
+pub fn validate_css(css: &str) -> Result<(), String> {
+    if css.trim().is_empty() {
+        return Err("theme is empty".to_string());
+    }
+
+    let mut depth = 0i64;
+    let mut chars = css.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = '\0';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(next) => last = next,
+                        None => return Err("unterminated comment".to_string()),
+                    }
+                }
+            }
+            '"' | '\'' => loop {
+                match chars.next() {
+                    Some('\\') => {
+                        chars.next();
+                    }
+                    Some(next) if next == c => break,
+                    Some('\n') | None => return Err("unterminated string".to_string()),
+                    Some(_) => {}
+                }
+            },
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return Err("unexpected }".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format!("{depth} unclosed block(s)"));
+    }
+    Ok(())
+}
+
+fn read_info(dir: &Path, theme: &ThemeEntry, listed: bool, cfg: &AppConfig) -> ThemeInfo {
+    let mut info = ThemeInfo {
+        file: theme.file.clone(),
+        path: theme_path(dir, theme).to_string_lossy().to_string(),
+        url: theme.url.clone(),
+        listed,
+        enabled: listed && theme.enabled,
+        meta: ThemeMeta::default(),
+        error: None,
+        blocked: Vec::new(),
+    };
+    match load_css(dir, theme, cfg) {
+        Ok(scanned) => {
+            info.meta = parse_meta(&scanned.css);
+            info.error = validate_css(&scanned.css).err();
+            info.blocked = scanned.blocked;
+        }
+        Err(err) => info.error = Some(err),
+    }
+    info
+}
+
+/// The active profile's themes in order, followed by any other `.css`
+/// files in the library.
+pub fn list(app: &AppHandle) -> Result<Vec<ThemeInfo>, String> {
+    let dir = themes_dir(app)?;
+    let cfg = app.state::<SettingsStore>().get();
+
+    let mut themes = cfg
+        .themes
+        .iter()
+        .map(|theme| read_info(&dir, theme, true, &cfg))
+        .collect::<Vec<_>>();
+
+    let mut unlisted = fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "css"))
+        .filter_map(|path| {
+            let file = path.file_name()?.to_string_lossy().to_string();
+            let listed = cfg.themes.iter().any(|theme| theme.file == file);
+            let theme = ThemeEntry {
+                file,
+                enabled: false,
+                url: None,
+            };
+            (!listed).then(|| read_info(&dir, &theme, false, &cfg))
+        })
+        .collect::<Vec<_>>();
+    unlisted.sort_by(|a, b| a.file.cmp(&b.file));
+    themes.extend(unlisted);
+    Ok(themes)
+}
+
+/// Downloaded remote themes and imports, kept so themes work offline.
+pub fn cache_dir(dir: &Path) -> PathBuf {
+    dir.join(".cache")
+}
+
+/// Library entries are relative to the themes dir; absolute ones (from
+/// before the library existed) replace it when joined. URL themes live in
+/// the download cache.
+pub fn theme_path(dir: &Path, theme: &ThemeEntry) -> PathBuf {
+    match theme.url.as_deref() {
+        Some(url) => theme_cache::cached_path(&cache_dir(dir), url),
+        None => dir.join(&theme.file),
+    }
+}
+
+/// Keeps only a plain file name so nothing can be written outside the
+/// themes dir.
+pub fn safe_file_name(name: &str) -> Option<String> {
+    let name = Path::new(name).file_name()?.to_string_lossy().to_string();
+    let name = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ' '))
+        .collect::<String>();
+    let name = name.trim_start_matches('.').trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Reads a theme, enforces the size cap and strips remote references to
+/// hosts that are not allowed.
+pub fn load_css(dir: &Path, theme: &ThemeEntry, cfg: &AppConfig) -> Result<ScannedCss, String> {
+    let source = read_theme(dir, theme)?;
+    check_size(source.css.len(), cfg.theme_max_bytes)?;
+    Ok(scan_css(&source.css, &cfg.theme_allowed_hosts))
+}
+
+fn check_size(len: usize, max: u64) -> Result<(), String> {
+    if len as u64 > max {
+        return Err(format!(
+            "theme is {} KiB, over the {} KiB limit",
+            len / 1024,
+            max / 1024
+        ));
+    }
+    Ok(())
+}
+
+/// Reads every enabled theme in order. A theme that cannot be read is
+/// skipped so the others still apply.
+pub fn enabled_styles(app: &AppHandle, cfg: &AppConfig) -> Vec<ThemeStyle> {
+    let Ok(dir) = themes_dir(app) else {
+        return Vec::new();
+    };
+    cfg.themes
+        .iter()
+        .enumerate()
+        .filter(|(_, theme)| theme.enabled)
+        .filter_map(|(index, theme)| {
+            match load_css(&dir, theme, cfg) {
+                Ok(scanned) => {
+                    for blocked in &scanned.blocked {
+                        log::warn!("Theme {} blocked remote resource {blocked}", theme.file);
+                    }
+                    Some(ThemeStyle {
+                        id: format!("ghostcord-theme-{index}"),
+                        file: theme.file.clone(),
+                        css: scanned.css,
+                    })
+                }
+                Err(err) => {
+                    log::warn!("Failed to load theme {}: {err}", theme.file);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// The entry for a local theme file: its name when it is in the library,
+/// its absolute path otherwise.
+fn local_entry(dir: &Path, source: &Path) -> Result<ThemeEntry, String> {
+    let source = fs::canonicalize(source).map_err(|e| format!("{}: {e}", source.display()))?;
+    let in_library =
+        fs::canonicalize(dir).is_ok_and(|dir| source.parent() == Some(dir.as_path()));
+    let file = match source.file_name() {
+        Some(name) if in_library => name.to_string_lossy().to_string(),
+        _ => source.to_string_lossy().to_string(),
+    };
+    Ok(ThemeEntry {
+        file,
+        enabled: true,
+        url: None,
+    })
+}
+
+/// Appends a theme, enabled. A file outside the library is listed where it
+/// is rather than copied, so edits to it and its local `@import`s keep hot
+/// reloading and relative imports keep resolving. Installing a theme that
+/// is already listed leaves the list as it is.
+pub fn install(app: &AppHandle, source: &Path) -> Result<AppConfig, String> {
+    let dir = themes_dir(app)?;
+    let entry = local_entry(&dir, source)?;
+    let name = source.display().to_string();
+
+    let theme = read_theme(&dir, &entry)?;
+    validate_css(&theme.css).map_err(|e| format!("{name} is not a valid theme: {e}"))?;
+    let max_bytes = app.state::<SettingsStore>().get().theme_max_bytes;
+    check_size(theme.css.len(), max_bytes).map_err(|e| format!("{name}: {e}"))?;
+
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+    match cfg.themes.iter().find(|theme| theme.file == entry.file) {
+        Some(theme) if theme.url.is_some() => {
+            return Err(format!("a theme named {} is already installed from a URL", entry.file));
+        }
+        Some(_) => {}
+        None => cfg.themes.push(entry),
+    }
+    let meta = parse_meta(&theme.css);
+    log::info!(
+        "Installed theme {name} ({} {})",
+        meta.name.as_deref().unwrap_or("unnamed"),
+        meta.version.as_deref().unwrap_or("unversioned")
+    );
+    settings::save_settings(app, &store, cfg)
+}
+
+/// Downloads a theme from `url` into the cache and appends it, enabled.
+/// Its name comes from the last path segment of the URL.
+pub async fn install_url(app: &AppHandle, url: &str) -> Result<AppConfig, String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("{url}: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("unsupported scheme {}", parsed.scheme()));
+    }
+    let url = parsed.to_string();
+
+    let dir = themes_dir(app)?;
+    let max_bytes = app.state::<SettingsStore>().get().theme_max_bytes;
+    if let Fetched::Offline(err) =
+        theme_cache::fetch(vencord::http_client(), &cache_dir(&dir), &url, max_bytes).await?
+    {
+        log::warn!("Using cached copy of {url}: {err}");
+    }
+    let css = fs::read_to_string(theme_cache::cached_path(&cache_dir(&dir), &url))
+        .map_err(|e| e.to_string())?;
+    validate_css(&css).map_err(|e| format!("{url} is not a valid theme: {e}"))?;
+
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+    check_size(css.len(), cfg.theme_max_bytes).map_err(|e| format!("{url}: {e}"))?;
+    if !cfg.themes.iter().any(|theme| theme.url.as_deref() == Some(url.as_str())) {
+        let base = parsed
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .and_then(safe_file_name)
+            .map(|name| name.trim_end_matches(".css").to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "remote".to_string());
+        let mut name = format!("{base}.css");
+        let mut n = 2;
+        while cfg.themes.iter().any(|theme| theme.file == name) || dir.join(&name).exists() {
+            name = format!("{base}-{n}.css");
+            n += 1;
+        }
+        cfg.themes.push(ThemeEntry {
+            file: name,
+            enabled: true,
+            url: Some(url.clone()),
+        });
+    }
+    log::info!("Installed theme from {url}");
+    settings::save_settings(app, &store, cfg)
+}
+
+/// Revalidates every remote stylesheet the enabled themes use, including
+/// remote `@import`s, and downloads the ones not cached yet. Returns whether
+/// anything changed on disk.
+pub async fn refresh_remote(app: &AppHandle) -> Result<bool, String> {
+    let dir = themes_dir(app)?;
+    let cache = cache_dir(&dir);
+    let cfg = app.state::<SettingsStore>().get();
+    let enabled = cfg
+        .themes
+        .iter()
+        .filter(|theme| cfg.enable_theme && theme.enabled)
+        .collect::<Vec<_>>();
+
+    let mut seen = Vec::<String>::new();
+    let mut changed = false;
+    let mut queue = enabled
+        .iter()
+        .filter_map(|theme| theme.url.clone())
+        .collect::<Vec<_>>();
+    // Each round can reveal the imports of what was just downloaded; the
+    // import depth limit bounds how many rounds there are.
+    while !queue.is_empty() {
+        for url in queue.drain(..) {
+            if seen.contains(&url) {
+                continue;
+            }
+            match theme_cache::fetch(vencord::http_client(), &cache, &url, cfg.theme_max_bytes)
+                .await
+            {
+                Ok(Fetched::Updated) => changed = true,
+                Ok(Fetched::NotModified) => {}
+                Ok(Fetched::Offline(err)) => log::warn!("Using cached copy of {url}: {err}"),
+                Err(err) => log::warn!("Failed to download theme: {err}"),
+            }
+            seen.push(url);
+        }
+        // Theme URLs were added by hand; imports inside them must be on
+        // an allowed host to be fetched at all.
+        queue = enabled
+            .iter()
+            .filter_map(|theme| read_theme(&dir, theme).ok())
+            .flat_map(|source| source.remote)
+            .filter(|url| !seen.contains(url))
+            .filter(|url| {
+                reqwest::Url::parse(url).ok().is_some_and(|url| {
+                    url.host_str()
+                        .is_some_and(|host| is_allowed_host(host, &cfg.theme_allowed_hosts))
+                })
+            })
+            .collect();
+        queue.dedup();
+    }
+    Ok(changed)
+}
+
+/// Runs [`refresh_remote`] off the main thread and re-applies the config
+/// when a download changed something.
+pub fn refresh_remote_in_background(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match refresh_remote(&app).await {
+            Ok(true) => {
+                let cfg = app.state::<SettingsStore>().get();
+                if let Err(err) = commands::apply_config_to_main(app.clone(), cfg) {
+                    log::warn!("Failed to apply refreshed themes: {err}");
+                }
+            }
+            Ok(false) => {}
+            Err(err) => log::warn!("Remote theme refresh failed: {err}"),
+        }
+    });
+}
+
+pub fn read_custom_css(app: &AppHandle) -> Result<String, String> {
+    let path = themes_dir(app)?.join(CUSTOM_CSS_FILE);
+    match fs::read_to_string(&path) {
+        Ok(css) => Ok(css),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(format!("{}: {err}", path.display())),
+    }
+}
+
+/// Writes the custom CSS box to its library file, listed last so it wins
+/// over the other themes. Saving it empty removes it.
+pub fn save_custom_css(app: &AppHandle, css: &str) -> Result<AppConfig, String> {
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+    let listed = cfg.themes.iter().any(|theme| theme.file == CUSTOM_CSS_FILE);
+    if css.trim().is_empty() {
+        return if listed {
+            remove(app, CUSTOM_CSS_FILE)
+        } else {
+            Ok(cfg)
+        };
+    }
+
+    validate_css(css)?;
+    check_size(css.len(), cfg.theme_max_bytes)?;
+    persist::write_atomic(&themes_dir(app)?.join(CUSTOM_CSS_FILE), css.as_bytes())?;
+    if !listed {
+        cfg.themes.push(ThemeEntry {
+            file: CUSTOM_CSS_FILE.to_string(),
+            enabled: true,
+            url: None,
+        });
+    }
+    let cfg = settings::save_settings(app, &store, cfg)?;
+    // The list may be unchanged, in which case no listener re-injects.
+    commands::apply_config_to_main(app.clone(), cfg.clone())?;
+    Ok(cfg)
+}
+
+/// Writes CSS that settings from before themes were files kept inline
+/// into the library at `dir`. A file that already holds the same CSS is
+/// reused, so adopting it twice does not leave copies behind. Returns the
+/// file name.
+fn write_inline_css(css: &str, dir: &Path) -> Result<String, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut name = CUSTOM_CSS_FILE.to_string();
+    let mut n = 2;
+    loop {
+        let path = dir.join(&name);
+        match fs::read_to_string(&path) {
+            Ok(existing) if existing == css => return Ok(name),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                persist::write_atomic(&path, css.as_bytes())?;
+                return Ok(name);
+            }
+            _ => {
+                name = format!("custom-{n}.css");
+                n += 1;
+            }
+        }
+    }
+}
+
+/// Moves inline CSS that the settings migration set aside into the library
+/// and lists it. If that fails it stays set aside for the next load.
+/// Returns whether `cfg` changed.
+pub fn adopt_pending_css(app: &AppHandle, cfg: &mut AppConfig) -> bool {
+    let Some(value) = cfg.extra.remove(PENDING_THEME_CSS_KEY) else {
+        return false;
+    };
+    let Some(css) = value.as_str() else {
+        return true;
+    };
+    match themes_dir(app).and_then(|dir| write_inline_css(css, &dir)) {
+        Ok(file) => {
+            log::info!("Moved inline theme CSS to {file}");
+            if !cfg.themes.iter().any(|theme| theme.file == file) {
+                cfg.themes.push(ThemeEntry {
+                    file,
+                    enabled: true,
+                    url: None,
+                });
+            }
+            true
+        }
+        Err(err) => {
+            log::warn!("Failed to move inline theme CSS: {err}");
+            cfg.extra.insert(PENDING_THEME_CSS_KEY.to_string(), value);
+            false
+        }
+    }
+}
+
+/// Drops a theme from the active profile. The library copy is deleted
+/// unless another profile still lists it.
+pub fn remove(app: &AppHandle, file: &str) -> Result<AppConfig, String> {
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+    let index = cfg
+        .themes
+        .iter()
+        .position(|theme| theme.file == file)
+        .ok_or_else(|| format!("theme {file} is not installed"))?;
+    let theme = cfg.themes.remove(index);
+    let cfg = settings::save_settings(app, &store, cfg)?;
+
+    // Downloads stay in the cache; outside files are not ours to delete.
+    if theme.url.is_some() || Path::new(&theme.file).is_absolute() {
+        return Ok(cfg);
+    }
+    let active = settings::active_profile(app);
+    let in_use = settings::list_profiles(app)?
+        .into_iter()
+        .filter(|profile| profile.name != active)
+        .filter_map(|profile| settings::read_profile(app, &profile.name).ok())
+        .any(|other| other.themes.iter().any(|entry| entry.file == theme.file));
+    if !in_use {
+        let path = theme_path(&themes_dir(app)?, &theme);
+        if let Err(err) = fs::remove_file(&path) {
+            log::warn!("Failed to delete {}: {err}", path.display());
+        }
+    }
+    log::info!("Removed theme {file}");
+    Ok(cfg)
+}
+
+pub fn set_enabled(app: &AppHandle, file: &str, enabled: bool) -> Result<AppConfig, String> {
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+    let theme = cfg
+        .themes
+        .iter_mut()
+        .find(|theme| theme.file == file)
+        .ok_or_else(|| format!("theme {file} is not installed"))?;
+    theme.enabled = enabled;
+    settings::save_settings(app, &store, cfg)
+}
+
+/// Puts the installed themes in the order of `files`, which must name each
+/// of them exactly once.
+pub fn reorder(app: &AppHandle, files: &[String]) -> Result<AppConfig, String> {
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+
+    let mut themes = Vec::with_capacity(files.len());
+    for file in files {
+        let index = cfg
+            .themes
+            .iter()
+            .position(|theme| &theme.file == file)
+            .ok_or_else(|| format!("theme {file} is not installed"))?;
+        themes.push(cfg.themes.remove(index));
+    }
+    if !cfg.themes.is_empty() {
+        return Err("the new order must list every installed theme".to_string());
+    }
+    cfg.themes = themes;
+    settings::save_settings(app, &store, cfg)
+}
+
+/// Where a stylesheet came from, which decides how its relative
+/// `@import`s resolve.
+#[derive(Debug, Clone, PartialEq)]
+enum Origin {
+    File(PathBuf),
+    Remote(reqwest::Url),
+}
+
+/// Reads an installed theme with its `@import`s inlined: local files from
+/// disk and remote URLs from the download cache. Remote imports that are
+/// not cached yet are left for the webview to fetch. Local imports must be
+/// `.css` files under the theme's own directory.
+pub fn read_theme(dir: &Path, theme: &ThemeEntry) -> Result<ThemeSource, String> {
+    let path = theme_path(dir, theme);
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    let root = path.parent().unwrap_or(dir).to_path_buf();
+    let origin = match theme.url.as_deref() {
+        Some(url) => Origin::Remote(reqwest::Url::parse(url).map_err(|e| format!("{url}: {e}"))?),
+        None => Origin::File(path),
+    };
+    let mut source = ThemeSource {
+        css: String::new(),
+        files: Vec::new(),
+        remote: Vec::new(),
+    };
+    source.css = read_inlined(&origin, &root, &cache_dir(dir), &mut source, 0)?;
+    Ok(source)
+}
+
+fn read_inlined(
+    origin: &Origin,
+    root: &Path,
+    cache: &Path,
+    source: &mut ThemeSource,
+    depth: usize,
+) -> Result<String, String> {
+    let css = match origin {
+        Origin::File(path) => {
+            let css = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            source.files.push(path.clone());
+            css
+        }
+        Origin::Remote(url) => {
+            source.remote.push(url.to_string());
+            let path = theme_cache::cached_path(cache, url.as_str());
+            fs::read_to_string(&path).map_err(|_| format!("{url} is not downloaded yet"))?
+        }
+    };
+    let css = unescape_names(&css);
+
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css.as_str();
+    while let Some(import) = find_import(rest) {
+        out.push_str(&rest[..import.start]);
+        let statement = &rest[import.start..import.end];
+
+        let resolved = resolve_import(origin, root, &unescape(&import.target));
+        let target = resolved.filter(|target| match target {
+            Origin::File(path) => !source.files.contains(path),
+            Origin::Remote(url) => !source.remote.iter().any(|seen| seen == url.as_str()),
+        });
+        match target {
+            Some(target) if depth < MAX_IMPORT_DEPTH => {
+                match read_inlined(&target, root, cache, source, depth + 1) {
+                    Ok(inlined) => {
+                        out.push_str(&format!("/* @import {} */\n", import.target));
+                        match import.media.as_deref() {
+                            Some(media) => out.push_str(&format!("@media {media} {{\n{inlined}\n}}\n")),
+                            None => {
+                                out.push_str(&inlined);
+                                out.push('\n');
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        log::warn!("Skipping theme import: {err}");
+                        out.push_str(statement);
+                    }
+                }
+            }
+            _ => out.push_str(statement),
+        }
+        rest = &rest[import.end..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+struct Import {
+    start: usize,
+    end: usize,
+    target: String,
+    media: Option<String>,
+}
+
+/// Finds the next `@import` statement outside comments and strings, in
+/// any case. A statement ends at `;`, at the `}` closing its block or at
+/// the end of the sheet, as it does for the webview. One that cannot be
+/// parsed is skipped.
+fn find_import(css: &str) -> Option<Import> {
+    let lower = css.to_ascii_lowercase();
+    let mut pos = 0;
+    while let Some(c) = css[pos..].chars().next() {
+        if lower[pos..].starts_with("/*") {
+            pos = comment_end(css, pos);
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            pos = string_end(css, pos);
+            continue;
+        }
+        if !lower[pos..].starts_with("@import") {
+            pos += c.len_utf8();
+            continue;
+        }
+
+        let start = pos;
+        let body_start = start + "@import".len();
+        let (body_end, end) = statement_end(css, body_start);
+        match parse_import_body(css[body_start..body_end].trim()) {
+            Some((target, media)) => {
+                return Some(Import {
+                    start,
+                    end,
+                    target,
+                    media,
+                })
+            }
+            None => pos = body_start,
+        }
+    }
+    None
+}
+
+/// Decodes the CSS escape starting with the backslash `text` begins with:
+/// the character and how many bytes the escape takes. A backslash before a
+/// newline or at the end is not an escape.
+fn decode_escape(text: &str) -> Option<(char, usize)> {
+    let rest = text.strip_prefix('\\')?;
+    let hex = rest
+        .char_indices()
+        .take_while(|(i, c)| *i < 6 && c.is_ascii_hexdigit())
+        .count();
+    if hex == 0 {
+        let c = rest.chars().next().filter(|c| *c != '\n')?;
+        return Some((c, 1 + c.len_utf8()));
+    }
+    let c = u32::from_str_radix(&rest[..hex], 16)
+        .ok()
+        .and_then(char::from_u32)
+        .filter(|c| *c != '\0')
+        .unwrap_or('\u{fffd}');
+    // One whitespace after a hex escape belongs to it.
+    let space = rest[hex..].chars().next().filter(|c| c.is_ascii_whitespace());
+    Some((c, 1 + hex + space.map_or(0, char::len_utf8)))
+}
+
+/// Decodes escapes in a string or `url()` target, as the webview would
+/// before fetching it.
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(c) = text[pos..].chars().next() {
+        match decode_escape(&text[pos..]) {
+            Some((decoded, len)) => {
+                out.push(decoded);
+                pos += len;
+            }
+            // An escaped newline continues a string.
+            None if c == '\\' && text[pos + 1..].starts_with('\n') => pos += 2,
+            None => {
+                out.push(c);
+                pos += c.len_utf8();
+            }
+        }
+    }
+    out
+}
+
+/// Decodes escaped letters in names outside strings and comments, so
+/// `\75 rl(` or `@\69mport` are seen as the `url(` and `@import` the webview
+/// reads them as. Other escapes, such as the `\:` of a class name, stay.
+fn unescape_names(css: &str) -> String {
+    if !css.contains('\\') {
+        return css.to_string();
+    }
+    let mut out = String::with_capacity(css.len());
+    let mut pos = 0;
+    while let Some(c) = css[pos..].chars().next() {
+        let end = match c {
+            '/' if css[pos..].starts_with("/*") => comment_end(css, pos),
+            '"' | '\'' => string_end(css, pos),
+            '\\' => match decode_escape(&css[pos..]) {
+                Some((decoded, len))
+                    if decoded.is_ascii_alphabetic()
+                        || (decoded == '-'
+                            && css[pos + len..].starts_with(|c: char| c.is_ascii_alphabetic())) =>
+                {
+                    out.push(decoded);
+                    pos += len;
+                    continue;
+                }
+                Some((_, len)) => pos + len,
+                None => pos + 1,
+            },
+            _ => pos + c.len_utf8(),
+        };
+        out.push_str(&css[pos..end]);
+        pos = end;
+    }
+    out
+}
+
+/// Index just past the comment opening at `from`, or the end of `css`.
+fn comment_end(css: &str, from: usize) -> usize {
+    css[from + 2..].find("*/").map_or(css.len(), |end| from + 2 + end + 2)
+}
+
+/// Index just past the string whose quote is at `from`. Escaped quotes do
+/// not end it; an unescaped newline does, as it does for the webview.
+fn string_end(css: &str, from: usize) -> usize {
+    let quote = css[from..].chars().next().unwrap_or_default();
+    let mut chars = css[from + 1..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return from + 1 + i,
+            c if c == quote => return from + 1 + i + 1,
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+/// Where the statement starting at `from` ends: the end of its body and of
+/// the statement. Terminators inside strings or parentheses do not count.
+fn statement_end(css: &str, from: usize) -> (usize, usize) {
+    let mut depth = 0usize;
+    let mut pos = from;
+    while let Some(c) = css[pos..].chars().next() {
+        match c {
+            '"' | '\'' => {
+                pos = string_end(css, pos);
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => return (pos, pos + 1),
+            '}' if depth == 0 => return (pos, pos),
+            _ => {}
+        }
+        pos += c.len_utf8();
+    }
+    (css.len(), css.len())
+}
+
+/// Splits `url("a.css") screen` / `"a.css"` into the target and optional
+/// media query.
+fn parse_import_body(body: &str) -> Option<(String, Option<String>)> {
+    let is_url = body.get(..4).is_some_and(|url| url.eq_ignore_ascii_case("url("));
+    let (target, rest) = if is_url {
+        let close = closing_paren(body, 4);
+        (body.get(4..close)?, body.get(close + 1..)?)
+    } else {
+        let quote = body.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = string_end(body, 0);
+        (body[1..end].strip_suffix(quote)?, &body[end..])
+    };
+    let target = target.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+    let media = Some(rest.trim()).filter(|media| !media.is_empty()).map(String::from);
+    Some((target, media))
+}
+
+pub fn is_allowed_host(host: &str, allowed: &[String]) -> bool {
+    let host = host.to_ascii_lowercase();
+    allowed.iter().any(|entry| {
+        let entry = entry.to_ascii_lowercase();
+        match entry.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{domain}")),
+            None => host == entry,
+        }
+    })
+}
+
+/// `Some(host)` for references the webview would fetch from the network.
+fn remote_host(target: &str) -> Option<String> {
+    let target = target.trim();
+    let url = match target.strip_prefix("//") {
+        Some(rest) => reqwest::Url::parse(&format!("https://{rest}")).ok()?,
+        None => reqwest::Url::parse(target).ok()?,
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    Some(url.host_str().unwrap_or_default().to_string())
+}
+
+/// Strips remote `@import`s and `url()`s that point at hosts outside
+/// `allowed`, leaving local, relative and `data:` references alone.
+pub fn scan_css(css: &str, allowed: &[String]) -> ScannedCss {
+    let css = unescape_names(css);
+    let mut blocked = Vec::new();
+    let is_blocked = |target: &str| {
+        remote_host(&unescape(target)).is_some_and(|host| !is_allowed_host(&host, allowed))
+    };
+
+    let mut without_imports = String::with_capacity(css.len());
+    let mut rest = css.as_str();
+    while let Some(import) = find_import(rest) {
+        without_imports.push_str(&rest[..import.start]);
+        if is_blocked(&import.target) {
+            blocked.push(import.target);
+            without_imports.push_str("/* ghostcord: blocked @import */");
+        } else {
+            without_imports.push_str(&rest[import.start..import.end]);
+        }
+        rest = &rest[import.end..];
+    }
+    without_imports.push_str(rest);
+
+    let source = without_imports.as_str();
+    let lower = source.to_ascii_lowercase();
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    let mut pos = 0;
+    while pos < source.len() {
+        if lower[pos..].starts_with("/*") {
+            pos = comment_end(source, pos);
+            continue;
+        }
+        if lower[pos..].starts_with(['"', '\'']) {
+            pos = string_end(source, pos);
+            continue;
+        }
+        let follows_word = lower[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+        if lower[pos..].starts_with("image-set(") && !follows_word {
+            let start = if lower[..pos].ends_with("-webkit-") {
+                pos - "-webkit-".len()
+            } else {
+                pos
+            };
+            let args_start = pos + "image-set(".len();
+            let close = closing_paren(source, args_start);
+            let found = image_set_targets(&source[args_start..close])
+                .into_iter()
+                .filter(|target| is_blocked(target))
+                .map(String::from)
+                .collect::<Vec<_>>();
+            if !found.is_empty() {
+                blocked.extend(found);
+                out.push_str(&source[copied..start]);
+                out.push_str("none");
+                copied = (close + 1).min(source.len());
+            }
+            pos = close + 1;
+            continue;
+        }
+        let starts_url =
+            lower[pos..].starts_with("url(") && !(follows_word || lower[..pos].ends_with('-'));
+        if !starts_url {
+            pos += lower[pos..].chars().next().map_or(1, char::len_utf8);
+            continue;
+        }
+
+        let inner_start = pos + "url(".len();
+        let inner_end = closing_paren(source, inner_start);
+        if inner_end == source.len() {
+            break;
+        }
+        let target = source[inner_start..inner_end]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'');
+        if is_blocked(target) {
+            blocked.push(target.to_string());
+            out.push_str(&source[copied..pos]);
+            out.push_str("none");
+            copied = inner_end + 1;
+        }
+        pos = inner_end + 1;
+    }
+    out.push_str(&source[copied..]);
+
+    ScannedCss { css: out, blocked }
+}
+
+/// Index of the `)` closing a parenthesis opened just before `from`, or the
+/// end of `css` if it is never closed. Parentheses in strings do not count.
+fn closing_paren(css: &str, from: usize) -> usize {
+    let mut depth = 1usize;
+    let mut pos = from;
+    while let Some(c) = css[pos..].chars().next() {
+        match c {
+            '"' | '\'' => {
+                pos = string_end(css, pos);
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return pos;
+                }
+            }
+            _ => {}
+        }
+        pos += c.len_utf8();
+    }
+    css.len()
+}
+
+/// The images an `image-set()` names, as bare strings or `url()`s.
+fn image_set_targets(args: &str) -> Vec<&str> {
+    let lower = args.to_ascii_lowercase();
+    let mut targets = Vec::new();
+    let mut at = 0;
+    while let Some(c) = args[at..].chars().next() {
+        if c == '"' || c == '\'' {
+            let end = string_end(args, at);
+            let inner = &args[at + 1..end];
+            targets.push(inner.strip_suffix(c).unwrap_or(inner));
+            at = end;
+        } else if lower[at..].starts_with("url(") {
+            let close = closing_paren(args, at + "url(".len());
+            let target = args[at + "url(".len()..close].trim();
+            targets.push(target.trim_matches(|c| c == '"' || c == '\''));
+            at = (close + 1).min(args.len());
+        } else {
+            at += c.len_utf8();
+        }
+    }
+    targets
+}
+
+/// Resolves an import target against the stylesheet importing it. Local
+/// files may import http(s) URLs and `.css` files under `root`, so a theme
+/// cannot pull other files into the page; remote sheets only URLs.
+fn resolve_import(origin: &Origin, root: &Path, target: &str) -> Option<Origin> {
+    if target.starts_with("data:") {
+        return None;
+    }
+    let remote = |url: reqwest::Url| {
+        matches!(url.scheme(), "http" | "https").then_some(Origin::Remote(url))
+    };
+    match origin {
+        Origin::Remote(base) => base.join(target).ok().and_then(remote),
+        Origin::File(path) => {
+            if let Some(rest) = target.strip_prefix("//") {
+                return reqwest::Url::parse(&format!("https://{rest}")).ok().and_then(remote);
+            }
+            match reqwest::Url::parse(target) {
+                Ok(url) => remote(url),
+                Err(_) => {
+                    let path = fs::canonicalize(path.parent()?.join(target)).ok()?;
+                    let is_css = path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("css"));
+                    (is_css && path.starts_with(root) && path.is_file())
+                        .then_some(Origin::File(path))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_betterdiscord_header() {
+        let css = "/**\n * @name Midnight\n * @author refact0r\n * @version 1.6.2\n * @description A dark, rounded discord theme.\n * @source https://github.com/refact0r/midnight-discord\n * @invite nz87hXyvcy\n*/\n\nbody { color: red; }\n";
+        let meta = parse_meta(css);
+        assert_eq!(meta.name.as_deref(), Some("Midnight"));
+        assert_eq!(meta.author.as_deref(), Some("refact0r"));
+        assert_eq!(meta.version.as_deref(), Some("1.6.2"));
+        assert_eq!(
+            meta.description.as_deref(),
+            Some("A dark, rounded discord theme.")
+        );
+        assert_eq!(
+            meta.source.as_deref(),
+            Some("https://github.com/refact0r/midnight-discord")
+        );
+    }
+
+    #[test]
+    fn missing_header_gives_empty_meta() {
+        assert_eq!(
+            parse_meta("body {}\n/** @name Late */"),
+            ThemeMeta::default()
+        );
+        assert_eq!(
+            parse_meta("/* @name Plain comment */"),
+            ThemeMeta::default()
+        );
+    }
+
+    #[test]
+    fn scan_strips_remote_references_to_unknown_hosts() {
+        let allowed = vec![
+            "fonts.googleapis.com".to_string(),
+            "*.github.io".to_string(),
+        ];
+        let css = concat!(
+            "@import url(\"https://fonts.googleapis.com/css2?family=Inter\");\n",
+            "@import 'https://evil.example/track.css';\n",
+            "/* url(https://evil.example/in-comment.png) */\n",
+            "a { background: URL( 'https://evil.example/pixel.png' ) no-repeat; }\n",
+            "b { background: url(https://user.github.io/bg.png), url(data:image/png;base64,AA==); }\n",
+            "c { background: url(//evil.example/x.png); mask: url(local.svg); }\n",
+        );
+        let scanned = scan_css(css, &allowed);
+        assert_eq!(
+            scanned.blocked,
+            [
+                "https://evil.example/track.css",
+                "https://evil.example/pixel.png",
+                "//evil.example/x.png",
+            ]
+        );
+        assert!(scanned.css.contains("fonts.googleapis.com"));
+        assert!(scanned.css.contains("/* ghostcord: blocked @import */"));
+        assert!(scanned.css.contains("a { background: none no-repeat; }"));
+        assert!(scanned.css.contains("url(https://user.github.io/bg.png)"));
+        assert!(scanned.css.contains("url(local.svg)"));
+        assert!(scanned.css.contains("in-comment.png"));
+    }
+
+    #[test]
+    fn scan_blocks_imports_in_any_form_the_webview_loads() {
+        let allowed = vec!["fonts.googleapis.com".to_string()];
+        let scanned = scan_css(
+            concat!(
+                "@IMPORT url(https://evil.example/upper.css);\n",
+                "@import ;\n",
+                "@media print { @import 'https://evil.example/block.css' }\n",
+                "@import \"https://evil.example/eof.css\"",
+            ),
+            &allowed,
+        );
+        assert_eq!(
+            scanned.blocked,
+            [
+                "https://evil.example/upper.css",
+                "https://evil.example/block.css",
+                "https://evil.example/eof.css",
+            ]
+        );
+        assert!(!scanned.css.contains("evil.example"));
+        assert!(scanned.css.contains("@import ;"));
+        assert!(scanned
+            .css
+            .contains("@media print { /* ghostcord: blocked @import */}"));
+    }
+
+    #[test]
+    fn scan_reads_strings_the_way_the_webview_does() {
+        let allowed = vec!["fonts.googleapis.com".to_string()];
+        let scanned = scan_css(
+            concat!(
+                "a { background: url(\"a).png\") url(https://evil.example/a.png); }\n",
+                "b { content: \"/*\"; background: url(https://evil.example/b.png); }\n",
+                "c { content: \"\\\" @import 'https://evil.example/c.css';\" }\n",
+                "@import \"x\\\";y.css\" screen; d { background: url(https://evil.example/d.png); }\n",
+                "/* */\n",
+            ),
+            &allowed,
+        );
+        assert_eq!(
+            scanned.blocked,
+            [
+                "https://evil.example/a.png",
+                "https://evil.example/b.png",
+                "https://evil.example/d.png",
+            ]
+        );
+        assert!(scanned.css.contains("url(\"a).png\") none;"));
+        assert!(scanned.css.contains("@import \"x\\\";y.css\" screen;"));
+    }
+
+    #[test]
+    fn scan_sees_through_css_escapes() {
+        let allowed = vec!["fonts.googleapis.com".to_string()];
+        let scanned = scan_css(
+            concat!(
+                "a { background: \\75 rl(https://evil.example/a.png); }\n",
+                "@\\69mport \"https://evil.example/b.css\";\n",
+                "c { background: u\\rl(https://evil.example/c.png); }\n",
+                "d { background: url(\"\\68ttps://evil.example/d.png\"); }\n",
+                "e { background: image\\-set(\"https\\3a //evil.example/e.png\" 1x); }\n",
+                ".sm\\:flex, .\\31 0 { content: \"\\f101\"; }\n",
+            ),
+            &allowed,
+        );
+        assert_eq!(
+            scanned.blocked,
+            [
+                "https://evil.example/b.css",
+                "https://evil.example/a.png",
+                "https://evil.example/c.png",
+                "\\68ttps://evil.example/d.png",
+                "https\\3a //evil.example/e.png",
+            ]
+        );
+        assert!(!scanned.css.contains("evil.example"));
+        assert!(scanned
+            .css
+            .contains(".sm\\:flex, .\\31 0 { content: \"\\f101\"; }"));
+    }
+
+    #[test]
+    fn scan_checks_bare_strings_in_image_sets() {
+        let allowed = vec!["fonts.googleapis.com".to_string()];
+        let scanned = scan_css(
+            concat!(
+                "a { background: image-set(\"https://evil.example/a.png\" 1x, \"local.png\" 2x); }\n",
+                "b { background: -webkit-image-set(url(https://evil.example/b.png) 1x); }\n",
+                "c { background: image-set(\"c.png\" 1x, url(c@2x.png) 2x); }\n",
+            ),
+            &allowed,
+        );
+        assert_eq!(
+            scanned.blocked,
+            ["https://evil.example/a.png", "https://evil.example/b.png"]
+        );
+        assert!(scanned.css.contains("a { background: none; }"));
+        assert!(scanned.css.contains("b { background: none; }"));
+        assert!(scanned
+            .css
+            .contains("image-set(\"c.png\" 1x, url(c@2x.png) 2x)"));
+    }
+
+    #[test]
+    fn allowlist_wildcards_match_subdomains_only_by_suffix() {
+        let allowed = vec!["*.github.io".to_string()];
+        assert!(is_allowed_host("user.github.io", &allowed));
+        assert!(is_allowed_host("github.io", &allowed));
+        assert!(!is_allowed_host("evilgithub.io", &allowed));
+    }
+
+    #[test]
+    fn safe_file_names_stay_inside_the_themes_dir() {
+        assert_eq!(
+            safe_file_name("dark.theme.css").as_deref(),
+            Some("dark.theme.css")
+        );
+        assert_eq!(
+            safe_file_name("/home/me/My Theme.css").as_deref(),
+            Some("My Theme.css")
+        );
+        assert_eq!(
+            safe_file_name("../../etc/passwd").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(safe_file_name(".hidden.css").as_deref(), Some("hidden.css"));
+        assert_eq!(safe_file_name("a<b>:c?.css").as_deref(), Some("abc.css"));
+        assert_eq!(safe_file_name(".."), None);
+        assert_eq!(safe_file_name("/"), None);
+        assert_eq!(safe_file_name("***"), None);
+    }
+
+    fn theme_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ghostcord-themes-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, css) in files {
+            fs::write(dir.join(file), css).unwrap();
+        }
+        dir
+    }
+
+    fn local(file: &str) -> ThemeEntry {
+        ThemeEntry {
+            file: file.to_string(),
+            enabled: true,
+            url: None,
+        }
+    }
+
+    #[test]
+    fn import_bodies_take_url_and_string_forms() {
+        let parse = |body| parse_import_body(body).unwrap();
+        assert_eq!(parse("url(\"a.css\")"), ("a.css".to_string(), None));
+        assert_eq!(parse("url( b.css )"), ("b.css".to_string(), None));
+        assert_eq!(parse("'c.css'"), ("c.css".to_string(), None));
+        assert_eq!(parse("URL(e.css)"), ("e.css".to_string(), None));
+        assert_eq!(
+            parse("\"d.css\" screen and (min-width: 600px)"),
+            (
+                "d.css".to_string(),
+                Some("screen and (min-width: 600px)".to_string())
+            )
+        );
+        assert!(parse_import_body("d.css").is_none());
+    }
+
+    #[test]
+    fn local_imports_are_inlined() {
+        let dir = theme_dir(
+            "inline",
+            &[
+                (
+                    "main.css",
+                    "/* @import \"commented.css\"; */\n@import url(\"a.css\");\n@import 'b.css' print;\nmain {}\n",
+                ),
+                ("a.css", "a {}"),
+                ("b.css", "b {}"),
+                ("commented.css", "commented {}"),
+            ],
+        );
+        let source = read_theme(&dir, &local("main.css")).unwrap();
+        assert!(source.css.contains("/* @import a.css */\na {}"));
+        assert!(source.css.contains("@media print {\nb {}\n}"));
+        assert!(source.css.contains("main {}"));
+        assert!(!source.css.contains("commented {}"));
+        assert_eq!(
+            source.files,
+            [dir.join("main.css"), dir.join("a.css"), dir.join("b.css")]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn themes_outside_the_library_keep_their_imports() {
+        let library = theme_dir("library", &[("kept.css", "kept {}")]);
+        let outside = theme_dir(
+            "outside",
+            &[("main.css", "@import \"parts/a.css\";\nmain {}")],
+        );
+        fs::create_dir_all(outside.join("parts")).unwrap();
+        fs::write(outside.join("parts/a.css"), "a {}").unwrap();
+
+        let entry = local_entry(&library, &outside.join("main.css")).unwrap();
+        assert!(Path::new(&entry.file).is_absolute());
+        let source = read_theme(&library, &entry).unwrap();
+        assert!(source.css.contains("/* @import parts/a.css */\na {}"));
+        let part = fs::canonicalize(outside.join("parts/a.css")).unwrap();
+        assert!(source.files.contains(&part));
+
+        let entry = local_entry(&library, &library.join("kept.css")).unwrap();
+        assert_eq!(entry.file, "kept.css");
+        let _ = fs::remove_dir_all(&library);
+        let _ = fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn inline_css_is_written_once() {
+        let dir = theme_dir("inline", &[(CUSTOM_CSS_FILE, "other {}")]);
+        assert_eq!(write_inline_css("a {}", &dir).unwrap(), "custom-2.css");
+        assert_eq!(
+            fs::read_to_string(dir.join("custom-2.css")).unwrap(),
+            "a {}"
+        );
+
+        // Loading the same settings again reuses the file.
+        assert_eq!(write_inline_css("a {}", &dir).unwrap(), "custom-2.css");
+        assert!(!dir.join("custom-3.css").exists());
+        assert_eq!(write_inline_css("other {}", &dir).unwrap(), CUSTOM_CSS_FILE);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn local_imports_stay_inside_the_theme_dir() {
+        let outside = theme_dir("secret", &[("key.css", "secret {}"), ("id_ed25519", "key")]);
+        let dir = theme_dir("confined", &[("sub.css", "sub {}"), ("notes.txt", "notes")]);
+        let escape = format!("../ghostcord-themes-{}-secret", std::process::id());
+        let main = local("main.css");
+        let resolve =
+            |target: &str| resolve_import(&Origin::File(dir.join("main.css")), &dir, target);
+
+        assert_eq!(resolve("sub.css"), Some(Origin::File(dir.join("sub.css"))));
+        assert_eq!(
+            resolve("./sub.css"),
+            Some(Origin::File(dir.join("sub.css")))
+        );
+        assert_eq!(resolve("notes.txt"), None);
+        assert_eq!(resolve(&format!("{escape}/key.css")), None);
+        assert_eq!(resolve(&format!("{escape}/id_ed25519")), None);
+        assert_eq!(resolve(&format!("sub.css/../{escape}/key.css")), None);
+        let absolute = outside.join("key.css");
+        assert_eq!(resolve(&absolute.to_string_lossy()), None);
+        assert_eq!(resolve(&format!("file://{}", absolute.display())), None);
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside.join("key.css"), dir.join("link.css")).unwrap();
+            assert_eq!(resolve("link.css"), None);
+        }
+
+        fs::write(
+            dir.join("main.css"),
+            format!("@import \"{escape}/key.css\";\nmain {{}}"),
+        )
+        .unwrap();
+        let source = read_theme(&dir, &main).unwrap();
+        assert!(!source.css.contains("secret {}"));
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn import_cycles_and_missing_files_are_left_alone() {
+        let dir = theme_dir(
+            "cycle",
+            &[
+                (
+                    "main.css",
+                    "@import \"a.css\";\n@import \"missing.css\";\nmain {}",
+                ),
+                ("a.css", "@import \"main.css\";\na {}"),
+            ],
+        );
+        let source = read_theme(&dir, &local("main.css")).unwrap();
+        // The cycle stops at the file already being read.
+        assert!(source.css.contains("@import \"main.css\";\na {}"));
+        assert!(source.css.contains("@import \"missing.css\";"));
+        assert_eq!(source.css.matches("main {}").count(), 1);
+        assert!(read_theme(&dir, &local("gone.css")).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn validates_braces_outside_comments_and_strings() {
+        assert!(validate_css("a { content: \"}\"; } /* { */").is_ok());
+        assert!(validate_css("a { color: red;").is_err());
+        assert!(validate_css("a { } }").is_err());
+        assert!(validate_css("a { content: \"oops; }").is_err());
+        assert!(validate_css("  \n").is_err());
+    }
+}
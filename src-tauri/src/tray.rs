@@ -0,0 +1,136 @@
+use tauri::{
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, Wry,
+};
+
+use crate::{
+    config::AppConfig,
+    settings::{self, SettingsStore},
+    state, windows,
+};
+
+const TRAY_ID: &str = "main";
+
+/// Tray menu toggles and the setting each one flips.
+const TOGGLES: &[(&str, &str)] = &[
+    ("enable_vencord", "Vencord"),
+    ("enable_blockers", "Blockers"),
+    ("enable_perf_css", "Performance CSS"),
+];
+
+/// Adds the tray icon. Without one (e.g. no tray host on the desktop),
+/// close-to-tray and start-minimized fall back to normal window behaviour.
+pub fn create(app: &AppHandle) -> tauri::Result<()> {
+    let show = MenuItem::with_id(app, "show", "Show/Hide Window", true, None::<&str>)?;
+    let checks = TOGGLES
+        .iter()
+        .map(|&(key, label)| CheckMenuItem::with_id(app, key, label, true, false, None::<&str>))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    sync_checks(&checks, &app.state::<SettingsStore>().get());
+    let reload = MenuItem::with_id(app, "reload", "Reload Discord", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let separators = [
+        PredefinedMenuItem::separator(app)?,
+        PredefinedMenuItem::separator(app)?,
+    ];
+
+    let mut items: Vec<&dyn IsMenuItem<Wry>> = vec![&show, &separators[0]];
+    items.extend(checks.iter().map(|check| check as &dyn IsMenuItem<Wry>));
+    items.extend([&separators[1] as &dyn IsMenuItem<Wry>, &reload, &quit]);
+    let menu = Menu::with_items(app, &items)?;
+
+    let menu_checks = checks.clone();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Ghostcord Lite")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| on_menu_event(app, event, &menu_checks))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                toggle_main(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    // Keep the checks in line with changes made from the settings panel.
+    app.state::<SettingsStore>().subscribe(
+        &["enable_vencord", "enable_blockers", "enable_perf_css"],
+        move |_, cfg| sync_checks(&checks, cfg),
+    );
+    Ok(())
+}
+
+fn sync_checks(checks: &[CheckMenuItem<Wry>], cfg: &AppConfig) {
+    let mut cfg = cfg.clone();
+    for (check, &(key, _)) in checks.iter().zip(TOGGLES) {
+        if let Some(value) = cfg.injection_mut(key) {
+            let _ = check.set_checked(*value);
+        }
+    }
+}
+
+pub fn is_available(app: &AppHandle) -> bool {
+    app.tray_by_id(TRAY_ID).is_some()
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent, checks: &[CheckMenuItem<Wry>]) {
+    match event.id().as_ref() {
+        "show" => toggle_main(app),
+        "reload" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.eval("window.location.reload()");
+            }
+        }
+        "quit" => {
+            let store = app.state::<state::StateStore>();
+            let _ = state::save_state(app, &store, store.get());
+            app.exit(0);
+        }
+        key => {
+            if let Err(err) = flip_setting(app, key) {
+                log::warn!("Failed to toggle {key} from the tray: {err}");
+                // The menu already flipped the check; put it back.
+                sync_checks(checks, &app.state::<SettingsStore>().get());
+            }
+        }
+    }
+}
+
+fn flip_setting(app: &AppHandle, key: &str) -> Result<(), String> {
+    let store = app.state::<SettingsStore>();
+    let mut cfg = store.get();
+    let value = cfg
+        .injection_mut(key)
+        .ok_or_else(|| format!("unknown tray item {key}"))?;
+    *value = !*value;
+    let vencord_disabled = key == "enable_vencord" && !cfg.enable_vencord;
+    settings::save_settings(app, &store, cfg)?;
+
+    // Vencord cannot be unloaded from a running page.
+    if vencord_disabled {
+        if let Some(window) = app.get_webview_window("main") {
+            window.eval("window.location.reload()").map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+pub fn toggle_main(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        windows::show_main(app);
+    }
+}
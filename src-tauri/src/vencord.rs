@@ -1,14 +1,55 @@
-use std::{fs, path::PathBuf};
+use std::{
+    cmp::Reverse,
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
 
-use crate::settings;
+use crate::{
+    config::{AppConfig, VencordSourceKind},
+    persist, settings,
+};
 
-const VENCORD_RELEASE_API: &str =
-    "https://api.github.com/repos/Vencord/Vencord/releases/latest";
+const VENCORD_RELEASES_API: &str = "https://api.github.com/repos/Vencord/Vencord/releases";
+const DEFAULT_USER_AGENT: &str = "ghostcord-lite";
 const VENCORD_PRELOAD_NAME: &str = "VencordDesktopPreload.js";
 const VENCORD_MAIN_NAME: &str = "VencordDesktopMain.js";
+const MAX_INSTALLED_VERSIONS: usize = 3;
+const DEFAULT_UPDATE_INTERVAL_MINUTES: u64 = 6 * 60;
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_RETRIES: u32 = 3;
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How often a waiting request checks whether it was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+/// Minimum gap between two progress events for the same download.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+pub const VENCORD_UPDATE_EVENT: &str = "ghostcord://vencord-update-available";
+pub const VENCORD_PROGRESS_EVENT: &str = "ghostcord://vencord-progress";
+
+static UPDATE_CHECKS_STARTED: AtomicBool = AtomicBool::new(false);
+/// Bumped by `cancel_downloads`; requests started under an older value stop.
+static CANCEL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Shared by every download so connections and TLS sessions are reused.
+pub fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .unwrap_or_default()
+    })
+}
 
 #[derive(Debug, Serialize)]
 pub struct VencordAssets {
@@ -19,8 +60,67 @@ pub struct VencordAssets {
     pub cached: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Reported on `VENCORD_PROGRESS_EVENT` while an install is brought up to
+/// date.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum VencordProgress {
+    Checking,
+    Downloading {
+        name: String,
+        index: usize,
+        total: usize,
+        bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    Installed {
+        tag: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct VencordVersion {
+    pub id: String,
+    pub tag: String,
+    pub published_at: Option<String>,
+    pub installed_at: u64,
+    pub active: bool,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VencordUpdate {
+    pub current: Option<String>,
+    pub latest: String,
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstalledAsset {
+    name: String,
+    sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InstalledVersion {
+    id: String,
+    tag: String,
+    published_at: Option<String>,
+    installed_at: u64,
+    preload: InstalledAsset,
+    main: InstalledAsset,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct VencordManifest {
+    active: Option<String>,
+    installed: Vec<InstalledVersion>,
+}
+
+/// Manifest written before versioned installs existed; the assets lived
+/// directly in the `vencord` dir.
+#[derive(Debug, Deserialize)]
+struct LegacyManifest {
     version: String,
     preload_name: String,
     main_name: String,
@@ -29,6 +129,7 @@ struct VencordManifest {
 #[derive(Debug, Deserialize)]
 struct GithubRelease {
     tag_name: String,
+    published_at: Option<String>,
     assets: Vec<GithubAsset>,
 }
 
@@ -38,42 +139,482 @@ struct GithubAsset {
     browser_download_url: String,
 }
 
-fn vencord_dir(app: &AppHandle) -> Result<PathBuf, String> {
-    let dir = settings::state_path(app)?
-        .parent()
-        .ok_or("failed to resolve app data dir")?
-        .join("vencord");
+impl InstalledVersion {
+    fn matches(&self, release: &GithubRelease) -> bool {
+        self.tag == release.tag_name && self.published_at == release.published_at
+    }
+
+    fn to_assets(&self, dir: &Path, cached: bool) -> VencordAssets {
+        let version_dir = dir.join(&self.id);
+        VencordAssets {
+            version: self.tag.clone(),
+            dir: version_dir.to_string_lossy().to_string(),
+            preload_path: version_dir
+                .join(&self.preload.name)
+                .to_string_lossy()
+                .to_string(),
+            main_path: version_dir.join(&self.main.name).to_string_lossy().to_string(),
+            cached,
+        }
+    }
+}
+
+impl VencordManifest {
+    fn find(&self, id: &str) -> Option<&InstalledVersion> {
+        self.installed.iter().find(|entry| entry.id == id)
+    }
+
+    /// Resolves a pin against installed versions, matching either the install
+    /// id or the release tag (newest install wins for repeated tags).
+    fn resolve_pin(&self, pin: &str) -> Option<&InstalledVersion> {
+        self.find(pin).or_else(|| {
+            self.installed
+                .iter()
+                .filter(|entry| entry.tag == pin)
+                .max_by_key(|entry| entry.installed_at)
+        })
+    }
+
+    fn record(&mut self, version: InstalledVersion) {
+        self.installed.retain(|entry| entry.id != version.id);
+        self.active = Some(version.id.clone());
+        self.installed.push(version);
+    }
+}
+
+fn vencord_dir(data_dir: &Path) -> Result<PathBuf, String> {
+    let dir = data_dir.join("vencord");
     fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
     Ok(dir)
 }
 
-fn manifest_path(app: &AppHandle) -> Result<PathBuf, String> {
-    vencord_dir(app).map(|dir| dir.join("manifest.json"))
+fn manifest_path(dir: &Path) -> PathBuf {
+    dir.join("manifest.json")
 }
 
-fn read_manifest(path: &PathBuf) -> Option<VencordManifest> {
-    let contents = fs::read_to_string(path).ok()?;
-    serde_json::from_str::<VencordManifest>(&contents).ok()
+fn read_manifest(path: &Path, dir: &Path) -> VencordManifest {
+    if !persist::exists(path) {
+        return VencordManifest::default();
+    }
+    let value = match persist::read_json::<serde_json::Value>(path) {
+        Ok(value) => value,
+        Err(err) => {
+            log::warn!("Ignoring unreadable Vencord manifest: {err}");
+            return VencordManifest::default();
+        }
+    };
+    if let Ok(manifest) = serde_json::from_value::<VencordManifest>(value.clone()) {
+        return manifest;
+    }
+    match serde_json::from_value::<LegacyManifest>(value) {
+        Ok(legacy) => {
+            let manifest = migrate_legacy_manifest(legacy, dir);
+            if let Err(err) = write_manifest(path, &manifest) {
+                log::warn!("Failed to write migrated Vencord manifest: {err}");
+            }
+            manifest
+        }
+        Err(err) => {
+            log::warn!("Ignoring unreadable Vencord manifest: {err}");
+            VencordManifest::default()
+        }
+    }
 }
 
-fn write_manifest(path: &PathBuf, manifest: &VencordManifest) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| e.to_string())
+fn write_manifest(path: &Path, manifest: &VencordManifest) -> Result<(), String> {
+    persist::write_json(path, manifest)
 }
 
-fn assets_exist(dir: &PathBuf, preload_name: &str, main_name: &str) -> bool {
-    dir.join(preload_name).exists() && dir.join(main_name).exists()
+/// Moves assets from the flat pre-versioning layout into their own version
+/// directory so they show up as a rollback target.
+fn migrate_legacy_manifest(legacy: LegacyManifest, dir: &Path) -> VencordManifest {
+    let mut manifest = VencordManifest::default();
+    let id = install_id(&legacy.version, None);
+    let version_dir = dir.join(&id);
+    if fs::create_dir_all(&version_dir).is_err() {
+        return manifest;
+    }
+
+    let mut assets = Vec::new();
+    for name in [&legacy.preload_name, &legacy.main_name] {
+        let target = version_dir.join(name);
+        if fs::rename(dir.join(name), &target).is_err() {
+            return manifest;
+        }
+        match sha256_file(&target) {
+            Ok(sha256) => assets.push(InstalledAsset {
+                name: name.clone(),
+                sha256,
+            }),
+            Err(_) => return manifest,
+        }
+    }
+
+    let main = assets.pop();
+    let preload = assets.pop();
+    if let (Some(preload), Some(main)) = (preload, main) {
+        log::info!("Migrated legacy Vencord install {} to {}", legacy.version, id);
+        manifest.record(InstalledVersion {
+            id,
+            tag: legacy.version,
+            published_at: None,
+            installed_at: now_secs(),
+            preload,
+            main,
+        });
+    }
+    manifest
 }
 
-async fn fetch_release(client: &reqwest::Client) -> Result<GithubRelease, String> {
-    let response = client
-        .get(VENCORD_RELEASE_API)
-        .header("User-Agent", "ghostcord-lite")
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+fn install_id(tag: &str, published_at: Option<&str>) -> String {
+    let sanitize = |value: &str| {
+        value
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+            .collect::<String>()
+    };
+    match published_at {
+        Some(published_at) => format!("{}-{}", sanitize(tag), sanitize(published_at)),
+        None => sanitize(tag),
+    }
+}
 
-    response.json::<GithubRelease>().await.map_err(|e| e.to_string())
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+fn verify_install(dir: &Path, version: &InstalledVersion) -> Result<(), String> {
+    let version_dir = dir.join(&version.id);
+    for asset in [&version.preload, &version.main] {
+        let actual = sha256_file(&version_dir.join(&asset.name))
+            .map_err(|e| format!("{} {}: {e}", version.id, asset.name))?;
+        if actual != asset.sha256 {
+            return Err(format!(
+                "checksum mismatch for {} {}: expected {}, got {actual}",
+                version.id, asset.name, asset.sha256
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Drops the oldest installs beyond `MAX_INSTALLED_VERSIONS`, never touching
+/// the active or pinned version.
+fn prune_installs(dir: &Path, manifest: &mut VencordManifest, pin: Option<&str>) {
+    let keep_pinned = pin.and_then(|pin| manifest.resolve_pin(pin)).map(|v| v.id.clone());
+    let mut by_age = manifest.installed.clone();
+    by_age.sort_by_key(|entry| Reverse(entry.installed_at));
+
+    let mut kept = 0;
+    let mut removed = Vec::new();
+    for entry in by_age {
+        let protected = manifest.active.as_deref() == Some(entry.id.as_str())
+            || keep_pinned.as_deref() == Some(entry.id.as_str());
+        if protected || kept < MAX_INSTALLED_VERSIONS {
+            kept += 1;
+        } else {
+            removed.push(entry.id);
+        }
+    }
+
+    for id in &removed {
+        if let Err(err) = fs::remove_dir_all(dir.join(id)) {
+            log::warn!("Failed to remove old Vencord install {id}: {err}");
+        }
+    }
+    manifest.installed.retain(|entry| !removed.contains(&entry.id));
+}
+
+/// Where releases come from: the GitHub releases API, a GitHub-compatible
+/// mirror of it, or a static directory (http(s) or `file://`) holding
+/// `latest.json` and `tags/<tag>.json` in the GitHub release format.
+struct ReleaseSource {
+    kind: VencordSourceKind,
+    base_url: String,
+    auth_token: Option<String>,
+    user_agent: String,
+    timeout: Duration,
+    retries: u32,
+    client: reqwest::Client,
+}
+
+/// Why a single request attempt failed.
+enum FetchError {
+    /// Network trouble, timeouts and server errors; worth another attempt.
+    Transient(String),
+    Fatal(String),
+}
+
+enum Interrupted {
+    TimedOut,
+    Cancelled,
+}
+
+/// Awaits `future`, giving up once `limit` has passed or the requests of
+/// `generation` have been cancelled.
+async fn guarded<F: Future>(
+    future: F,
+    limit: Duration,
+    generation: u64,
+) -> Result<F::Output, Interrupted> {
+    let mut future = std::pin::pin!(future);
+    let mut waited = Duration::ZERO;
+    loop {
+        if CANCEL_GENERATION.load(Ordering::SeqCst) != generation {
+            return Err(Interrupted::Cancelled);
+        }
+        if waited >= limit {
+            return Err(Interrupted::TimedOut);
+        }
+        let slice = CANCEL_POLL.min(limit - waited);
+        if let Ok(output) = tokio::time::timeout(slice, &mut future).await {
+            return Ok(output);
+        }
+        waited += slice;
+    }
+}
+
+/// Delay before retry number `attempt` (starting at 1): 1s, 2s, 4s, ...
+/// capped at `MAX_BACKOFF`.
+fn backoff_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.saturating_sub(1).min(16)).min(MAX_BACKOFF)
+}
+
+fn is_transient(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Stops every Vencord request currently in flight. Later requests are not
+/// affected.
+pub fn cancel_downloads() {
+    CANCEL_GENERATION.fetch_add(1, Ordering::SeqCst);
+    log::info!("Cancelled Vencord downloads");
+}
+
+impl ReleaseSource {
+    fn from_config(cfg: &AppConfig) -> Self {
+        let source = &cfg.vencord_source;
+        let base_url = source
+            .base_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .unwrap_or(VENCORD_RELEASES_API)
+            .trim_end_matches('/')
+            .to_string();
+        Self {
+            kind: source.kind.clone(),
+            base_url,
+            auth_token: source.auth_token.clone().filter(|token| !token.is_empty()),
+            user_agent: source
+                .user_agent
+                .clone()
+                .filter(|agent| !agent.is_empty())
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+            timeout: Duration::from_secs(
+                cfg.vencord_timeout_seconds
+                    .unwrap_or(DEFAULT_TIMEOUT_SECONDS)
+                    .max(1),
+            ),
+            retries: cfg.vencord_download_retries.unwrap_or(DEFAULT_RETRIES),
+            client: http_client().clone(),
+        }
+    }
+
+    fn release_url(&self, pin: Option<&str>) -> Result<String, String> {
+        let tag = match pin {
+            Some("" | "." | "..") => return Err(format!("invalid Vencord pin {pin:?}")),
+            Some(tag) => Some(encode_path_segment(tag)),
+            None => None,
+        };
+        Ok(match (&self.kind, tag) {
+            (VencordSourceKind::Github, Some(tag)) => format!("{}/tags/{tag}", self.base_url),
+            (VencordSourceKind::Github, None) => format!("{}/latest", self.base_url),
+            (VencordSourceKind::Static, Some(tag)) => format!("{}/tags/{tag}.json", self.base_url),
+            (VencordSourceKind::Static, None) => format!("{}/latest.json", self.base_url),
+        })
+    }
+
+    /// Only sends the token to the configured host so it never leaks to
+    /// third-party asset hosts.
+    fn request(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .get(url)
+            .header("User-Agent", self.user_agent.as_str());
+        match &self.auth_token {
+            Some(token) if same_origin(url, &self.base_url) => request.bearer_auth(token),
+            _ => request,
+        }
+    }
+
+    /// Downloads `url`, retrying transient failures with backoff and
+    /// reporting `(bytes, total)` as the body streams in.
+    async fn fetch(
+        &self,
+        url: &str,
+        on_progress: impl Fn(u64, Option<u64>),
+    ) -> Result<Vec<u8>, String> {
+        if let Some(path) = file_url_path(url)? {
+            // Only a local source may point at local files; a remote release
+            // could otherwise read anything the user can.
+            if !self.base_url.starts_with("file://") {
+                return Err(format!("{url}: file:// is only allowed for a file:// source"));
+            }
+            let bytes = fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+            on_progress(bytes.len() as u64, Some(bytes.len() as u64));
+            return Ok(bytes);
+        }
+
+        let generation = CANCEL_GENERATION.load(Ordering::SeqCst);
+        let mut attempt = 0;
+        loop {
+            let err = match self.fetch_once(url, generation, &on_progress).await {
+                Ok(bytes) => return Ok(bytes),
+                Err(FetchError::Fatal(err)) => return Err(err),
+                Err(FetchError::Transient(err)) => err,
+            };
+            if attempt >= self.retries {
+                return Err(err);
+            }
+            attempt += 1;
+            let delay = backoff_delay(attempt);
+            log::warn!(
+                "{err}; retrying in {}s ({attempt}/{})",
+                delay.as_secs(),
+                self.retries
+            );
+            if let Err(Interrupted::Cancelled) =
+                guarded(tokio::time::sleep(delay), delay, generation).await
+            {
+                return Err("download cancelled".to_string());
+            }
+        }
+    }
+
+    async fn fetch_once(
+        &self,
+        url: &str,
+        generation: u64,
+        on_progress: &impl Fn(u64, Option<u64>),
+    ) -> Result<Vec<u8>, FetchError> {
+        let interrupted = |interrupted| match interrupted {
+            Interrupted::Cancelled => FetchError::Fatal("download cancelled".to_string()),
+            Interrupted::TimedOut => FetchError::Transient(format!(
+                "{url}: no response within {}s",
+                self.timeout.as_secs()
+            )),
+        };
+
+        let mut response = guarded(self.request(url).send(), self.timeout, generation)
+            .await
+            .map_err(interrupted)?
+            .map_err(|e| FetchError::Transient(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let err = format!("request failed for {url}: {status}");
+            return Err(if is_transient(status) {
+                FetchError::Transient(err)
+            } else {
+                FetchError::Fatal(err)
+            });
+        }
+
+        let total = response.content_length();
+        let mut bytes = Vec::with_capacity(total.unwrap_or_default().min(64 << 20) as usize);
+        let mut reported = Instant::now();
+        while let Some(chunk) = guarded(response.chunk(), self.timeout, generation)
+            .await
+            .map_err(interrupted)?
+            .map_err(|e| FetchError::Transient(format!("{url}: {e}")))?
+        {
+            bytes.extend_from_slice(&chunk);
+            if reported.elapsed() >= PROGRESS_INTERVAL {
+                on_progress(bytes.len() as u64, total);
+                reported = Instant::now();
+            }
+        }
+        on_progress(bytes.len() as u64, total.or(Some(bytes.len() as u64)));
+        Ok(bytes)
+    }
+
+    async fn fetch_release(&self, pin: Option<&str>) -> Result<GithubRelease, String> {
+        let url = self.release_url(pin)?;
+        let bytes = self
+            .fetch(&url, |_, _| {})
+            .await
+            .map_err(|e| format!("release lookup failed: {e}"))?;
+        let mut release = serde_json::from_slice::<GithubRelease>(&bytes)
+            .map_err(|e| format!("invalid release from {url}: {e}"))?;
+
+        if self.kind == VencordSourceKind::Static {
+            let base = reqwest::Url::parse(&url).map_err(|e| e.to_string())?;
+            for asset in &mut release.assets {
+                asset.browser_download_url = base
+                    .join(&asset.browser_download_url)
+                    .map_err(|e| format!("invalid asset url for {}: {e}", asset.name))?
+                    .to_string();
+            }
+        }
+
+        Ok(release)
+    }
+
+    async fn download_to_path(
+        &self,
+        url: &str,
+        path: &Path,
+        on_progress: impl Fn(u64, Option<u64>),
+    ) -> Result<String, String> {
+        let bytes = self
+            .fetch(url, on_progress)
+            .await
+            .map_err(|e| format!("download failed: {e}"))?;
+        persist::write_atomic(path, &bytes)?;
+        Ok(format!("{:x}", Sha256::digest(&bytes)))
+    }
+}
+
+fn file_url_path(url: &str) -> Result<Option<PathBuf>, String> {
+    if !url.starts_with("file://") {
+        return Ok(None);
+    }
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.to_file_path().ok())
+        .map(Some)
+        .ok_or_else(|| format!("invalid file url: {url}"))
+}
+
+/// Percent-encodes everything but unreserved characters, so a tag cannot
+/// reach outside its own path segment.
+fn encode_path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn same_origin(url: &str, base_url: &str) -> bool {
+    match (reqwest::Url::parse(url), reqwest::Url::parse(base_url)) {
+        (Ok(url), Ok(base)) => url.origin() == base.origin(),
+        _ => false,
+    }
 }
 
 fn pick_asset_url(release: &GithubRelease, name: &str, fallback_key: &str) -> Option<String> {
@@ -101,81 +642,608 @@ fn available_assets(release: &GithubRelease) -> String {
         .join(", ")
 }
 
-async fn download_to_path(
-    client: &reqwest::Client,
-    url: &str,
-    path: &PathBuf,
-) -> Result<(), String> {
-    let response = client
-        .get(url)
-        .header("User-Agent", "ghostcord-lite")
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+/// Receives progress while an install is brought up to date.
+pub type ProgressFn<'a> = &'a (dyn Fn(VencordProgress) + Sync);
 
-    let status = response.status();
-    if !status.is_success() {
-        return Err(format!("download failed for {url}: {status}"));
+fn emit_progress(app: &AppHandle, progress: VencordProgress) {
+    if let Err(err) = app.emit(VENCORD_PROGRESS_EVENT, &progress) {
+        log::warn!("Failed to emit Vencord progress: {err}");
     }
-
-    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
-    fs::write(path, bytes).map_err(|e| e.to_string())
 }
 
-pub async fn ensure_vencord_assets(app: &AppHandle) -> Result<VencordAssets, String> {
-    let dir = vencord_dir(app)?;
-    let manifest_file = manifest_path(app)?;
-
-    let client = reqwest::Client::new();
-    let release = fetch_release(&client).await?;
+/// Downloads asset `index` of the two every install has, reporting progress.
+async fn download_asset(
+    report: ProgressFn<'_>,
+    source: &ReleaseSource,
+    version_dir: &Path,
+    name: &str,
+    url: &str,
+    index: usize,
+) -> Result<String, String> {
+    let progress = |bytes, total_bytes| {
+        report(VencordProgress::Downloading {
+            name: name.to_string(),
+            index,
+            total: 2,
+            bytes,
+            total_bytes,
+        })
+    };
+    progress(0, None);
+    source
+        .download_to_path(url, &version_dir.join(name), progress)
+        .await
+}
 
-    let preload_url = pick_asset_url(&release, VENCORD_PRELOAD_NAME, "desktoppreload")
+async fn install_release(
+    report: ProgressFn<'_>,
+    source: &ReleaseSource,
+    dir: &Path,
+    release: &GithubRelease,
+) -> Result<InstalledVersion, String> {
+    let preload_url = pick_asset_url(release, VENCORD_PRELOAD_NAME, "desktoppreload")
         .ok_or_else(|| {
             format!(
                 "missing Vencord preload asset; available: {}",
-                available_assets(&release)
+                available_assets(release)
             )
         })?;
-    let main_url = pick_asset_url(&release, VENCORD_MAIN_NAME, "desktopmain")
+    let main_url = pick_asset_url(release, VENCORD_MAIN_NAME, "desktopmain")
         .ok_or_else(|| {
             format!(
                 "missing Vencord main asset; available: {}",
-                available_assets(&release)
+                available_assets(release)
             )
         })?;
 
-    if let Some(manifest) = read_manifest(&manifest_file) {
-        if manifest.version == release.tag_name
-            && assets_exist(&dir, &manifest.preload_name, &manifest.main_name)
-        {
-            return Ok(VencordAssets {
-                version: manifest.version,
-                dir: dir.to_string_lossy().to_string(),
-                preload_path: dir.join(&manifest.preload_name).to_string_lossy().to_string(),
-                main_path: dir.join(&manifest.main_name).to_string_lossy().to_string(),
-                cached: true,
-            });
+    let id = install_id(&release.tag_name, release.published_at.as_deref());
+    let version_dir = dir.join(&id);
+    fs::create_dir_all(&version_dir).map_err(|e| e.to_string())?;
+
+    let preload_sha =
+        download_asset(report, source, &version_dir, VENCORD_PRELOAD_NAME, &preload_url, 1)
+            .await?;
+    let main_sha =
+        download_asset(report, source, &version_dir, VENCORD_MAIN_NAME, &main_url, 2).await?;
+
+    Ok(InstalledVersion {
+        id,
+        tag: release.tag_name.clone(),
+        published_at: release.published_at.clone(),
+        installed_at: now_secs(),
+        preload: InstalledAsset {
+            name: VENCORD_PRELOAD_NAME.to_string(),
+            sha256: preload_sha,
+        },
+        main: InstalledAsset {
+            name: VENCORD_MAIN_NAME.to_string(),
+            sha256: main_sha,
+        },
+    })
+}
+
+/// Marks a verified install as active and returns its asset paths.
+fn use_install(
+    dir: &Path,
+    manifest_file: &Path,
+    manifest: &mut VencordManifest,
+    installed: &InstalledVersion,
+) -> Result<VencordAssets, String> {
+    verify_install(dir, installed)?;
+    if manifest.active.as_deref() != Some(installed.id.as_str()) {
+        manifest.active = Some(installed.id.clone());
+        write_manifest(manifest_file, manifest)?;
+    }
+    Ok(installed.to_assets(dir, true))
+}
+
+fn cached_install(
+    dir: &Path,
+    manifest_file: &Path,
+    manifest: &mut VencordManifest,
+    pin: Option<&str>,
+) -> Option<VencordAssets> {
+    let installed = match pin {
+        Some(pin) => manifest.resolve_pin(pin),
+        None => manifest.active.as_deref().and_then(|active| manifest.find(active)),
+    }?
+    .clone();
+
+    match use_install(dir, manifest_file, manifest, &installed) {
+        Ok(assets) => Some(assets),
+        Err(err) => {
+            log::warn!("Cached Vencord install is unusable: {err}");
+            None
+        }
+    }
+}
+
+/// Returns the Vencord assets to inject. In offline-first mode a verified
+/// cached install is used right away and the release check moves to the
+/// background; otherwise the release API is consulted first.
+pub async fn ensure_vencord_assets(app: &AppHandle) -> Result<VencordAssets, String> {
+    let cfg = app.state::<settings::SettingsStore>().get();
+    if cfg.vencord_offline_first {
+        let dir = vencord_dir(&settings::data_dir(app)?)?;
+        let manifest_file = manifest_path(&dir);
+        let mut manifest = read_manifest(&manifest_file, &dir);
+        let pin = cfg.vencord_pin.as_deref();
+        if let Some(assets) = cached_install(&dir, &manifest_file, &mut manifest, pin) {
+            start_update_checks(app);
+            return Ok(assets);
         }
     }
 
-    let preload_path = dir.join(VENCORD_PRELOAD_NAME);
-    let main_path = dir.join(VENCORD_MAIN_NAME);
+    sync_vencord_assets(app).await
+}
+
+/// Brings the active install in line with the release API (or the pin),
+/// downloading when needed. Falls back to the cached install when the
+/// network is unavailable.
+pub async fn sync_vencord_assets(app: &AppHandle) -> Result<VencordAssets, String> {
+    let cfg = app.state::<settings::SettingsStore>().get();
+    let report = |progress| emit_progress(app, progress);
+    sync_assets_in(&settings::data_dir(app)?, &cfg, &report).await
+}
+
+/// `sync_vencord_assets` for the install under `data_dir`, without an app.
+pub async fn sync_assets_in(
+    data_dir: &Path,
+    cfg: &AppConfig,
+    report: ProgressFn<'_>,
+) -> Result<VencordAssets, String> {
+    let dir = vencord_dir(data_dir)?;
+    let manifest_file = manifest_path(&dir);
+    let mut manifest = read_manifest(&manifest_file, &dir);
+    let pin = cfg.vencord_pin.clone();
+
+    if let Some(pin) = pin.as_deref() {
+        if let Some(installed) = manifest.resolve_pin(pin).cloned() {
+            match use_install(&dir, &manifest_file, &mut manifest, &installed) {
+                Ok(assets) => return Ok(assets),
+                Err(err) => log::warn!("Pinned Vencord install failed verification: {err}"),
+            }
+        }
+    }
+
+    let source = ReleaseSource::from_config(cfg);
+    report(VencordProgress::Checking);
+    let release = match source.fetch_release(pin.as_deref()).await {
+        Ok(release) => release,
+        Err(err) => {
+            log::warn!("Vencord release check failed, trying cached install: {err}");
+            return cached_install(&dir, &manifest_file, &mut manifest, pin.as_deref())
+                .ok_or(err);
+        }
+    };
+
+    if let Some(installed) = manifest
+        .installed
+        .iter()
+        .find(|entry| entry.matches(&release))
+        .cloned()
+    {
+        match use_install(&dir, &manifest_file, &mut manifest, &installed) {
+            Ok(assets) => return Ok(assets),
+            Err(err) => log::warn!("Reinstalling Vencord {}: {err}", installed.id),
+        }
+    }
+
+    let installed = install_release(report, &source, &dir, &release).await?;
+    log::info!("Installed Vencord {}", installed.id);
+    report(VencordProgress::Installed {
+        tag: installed.tag.clone(),
+    });
+    manifest.record(installed.clone());
+    prune_installs(&dir, &mut manifest, pin.as_deref());
+    write_manifest(&manifest_file, &manifest)?;
+
+    Ok(installed.to_assets(&dir, false))
+}
+
+/// Looks up the release the active install would move to, without
+/// downloading anything. Returns `None` when already up to date or pinned
+/// to an installed version.
+pub async fn check_for_update(app: &AppHandle) -> Result<Option<VencordUpdate>, String> {
+    let dir = vencord_dir(&settings::data_dir(app)?)?;
+    let manifest = read_manifest(&manifest_path(&dir), &dir);
+    let cfg = app.state::<settings::SettingsStore>().get();
+    let pin = cfg.vencord_pin.clone();
+    if pin
+        .as_deref()
+        .is_some_and(|pin| manifest.resolve_pin(pin).is_some())
+    {
+        return Ok(None);
+    }
+
+    let source = ReleaseSource::from_config(&cfg);
+    let release = source.fetch_release(pin.as_deref()).await?;
+    let active = manifest.active.as_deref().and_then(|active| manifest.find(active));
+    if active.is_some_and(|active| active.matches(&release)) {
+        return Ok(None);
+    }
+
+    Ok(Some(VencordUpdate {
+        current: active.map(|active| active.tag.clone()),
+        latest: release.tag_name,
+        published_at: release.published_at,
+    }))
+}
+
+/// Spawns the periodic background release check once per process.
+fn start_update_checks(app: &AppHandle) {
+    if UPDATE_CHECKS_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match check_for_update(&app).await {
+                Ok(Some(update)) => {
+                    log::info!("Vencord update available: {}", update.latest);
+                    if let Err(err) = app.emit(VENCORD_UPDATE_EVENT, &update) {
+                        log::warn!("Failed to emit Vencord update event: {err}");
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => log::warn!("Background Vencord update check failed: {err}"),
+            }
+
+            let minutes = app
+                .state::<settings::SettingsStore>()
+                .get()
+                .vencord_update_interval_minutes
+                .unwrap_or(DEFAULT_UPDATE_INTERVAL_MINUTES)
+                .max(1);
+            tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        }
+    });
+}
+
+pub fn list_installed_versions(app: &AppHandle) -> Result<Vec<VencordVersion>, String> {
+    let pin = app.state::<settings::SettingsStore>().get().vencord_pin;
+    installed_versions_in(&settings::data_dir(app)?, pin.as_deref())
+}
 
-    download_to_path(&client, &preload_url, &preload_path).await?;
-    download_to_path(&client, &main_url, &main_path).await?;
+/// Installs under `data_dir`, newest first.
+pub fn installed_versions_in(
+    data_dir: &Path,
+    pin: Option<&str>,
+) -> Result<Vec<VencordVersion>, String> {
+    let dir = vencord_dir(data_dir)?;
+    let manifest = read_manifest(&manifest_path(&dir), &dir);
+    let pinned_id = pin
+        .and_then(|pin| manifest.resolve_pin(pin))
+        .map(|entry| entry.id.clone());
 
-    let manifest = VencordManifest {
-        version: release.tag_name.clone(),
-        preload_name: VENCORD_PRELOAD_NAME.to_string(),
-        main_name: VENCORD_MAIN_NAME.to_string(),
+    let mut versions = manifest
+        .installed
+        .iter()
+        .map(|entry| VencordVersion {
+            id: entry.id.clone(),
+            tag: entry.tag.clone(),
+            published_at: entry.published_at.clone(),
+            installed_at: entry.installed_at,
+            active: manifest.active.as_deref() == Some(entry.id.as_str()),
+            pinned: pinned_id.as_deref() == Some(entry.id.as_str()),
+        })
+        .collect::<Vec<_>>();
+    versions.sort_by_key(|version| Reverse(version.installed_at));
+    Ok(versions)
+}
+
+/// Activates an installed version and pins its tag so the next startup does
+/// not move forward again. Without an explicit id, the install preceding the
+/// active one is used.
+pub fn rollback(app: &AppHandle, id: Option<String>) -> Result<VencordAssets, String> {
+    let dir = vencord_dir(&settings::data_dir(app)?)?;
+    let manifest_file = manifest_path(&dir);
+    let mut manifest = read_manifest(&manifest_file, &dir);
+
+    let target = match id {
+        Some(id) => manifest
+            .find(&id)
+            .cloned()
+            .ok_or_else(|| format!("Vencord version {id} is not installed"))?,
+        None => {
+            let active_installed_at = manifest
+                .active
+                .as_deref()
+                .and_then(|active| manifest.find(active))
+                .map(|entry| entry.installed_at)
+                .ok_or("no active Vencord version to roll back from")?;
+            manifest
+                .installed
+                .iter()
+                .filter(|entry| entry.installed_at < active_installed_at)
+                .max_by_key(|entry| entry.installed_at)
+                .cloned()
+                .ok_or("no previous Vencord version installed")?
+        }
     };
+
+    verify_install(&dir, &target)?;
+
+    // The tag still means something to the release source once the install
+    // is gone. Saved first, so a rejected save leaves the active install
+    // alone.
+    let store = app.state::<settings::SettingsStore>();
+    let mut cfg = store.get();
+    cfg.vencord_pin = Some(target.tag.clone());
+    settings::save_settings(app, &store, cfg)?;
+
+    manifest.active = Some(target.id.clone());
     write_manifest(&manifest_file, &manifest)?;
 
-    Ok(VencordAssets {
-        version: release.tag_name,
-        dir: dir.to_string_lossy().to_string(),
-        preload_path: preload_path.to_string_lossy().to_string(),
-        main_path: main_path.to_string_lossy().to_string(),
-        cached: false,
+    log::info!("Rolled back Vencord to {}", target.id);
+    Ok(target.to_assets(&dir, true))
+}
+
+/// Id of the install currently in use, if any.
+pub fn active_install(app: &AppHandle) -> Result<Option<String>, String> {
+    let dir = vencord_dir(&settings::data_dir(app)?)?;
+    Ok(read_manifest(&manifest_path(&dir), &dir).active)
+}
+
+/// Makes an install active again after verifying it, leaving the settings
+/// alone. Returns the install's release tag.
+pub fn activate_install(app: &AppHandle, id: &str) -> Result<String, String> {
+    let dir = vencord_dir(&settings::data_dir(app)?)?;
+    let manifest_file = manifest_path(&dir);
+    let mut manifest = read_manifest(&manifest_file, &dir);
+    let target = manifest
+        .find(id)
+        .cloned()
+        .ok_or_else(|| format!("Vencord version {id} is not installed"))?;
+    use_install(&dir, &manifest_file, &mut manifest, &target)?;
+    Ok(target.tag)
+}
+
+/// What a config bundle records about Vencord: tags rather than local
+/// install ids, so another machine can fetch the same releases.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VencordSnapshot {
+    pub active_tag: Option<String>,
+    pub pinned_tag: Option<String>,
+}
+
+pub fn snapshot(app: &AppHandle, pin: Option<&str>) -> Result<VencordSnapshot, String> {
+    let dir = vencord_dir(&settings::data_dir(app)?)?;
+    let manifest = read_manifest(&manifest_path(&dir), &dir);
+    Ok(VencordSnapshot {
+        active_tag: manifest
+            .active
+            .as_deref()
+            .and_then(|active| manifest.find(active))
+            .map(|entry| entry.tag.clone()),
+        pinned_tag: pin.map(|pin| {
+            manifest
+                .resolve_pin(pin)
+                .map(|entry| entry.tag.clone())
+                .unwrap_or_else(|| pin.to_string())
+        }),
     })
 }
+
+/// Whether `pin` refers to an install on this machine.
+pub fn is_installed(app: &AppHandle, pin: &str) -> bool {
+    settings::data_dir(app)
+        .and_then(|data_dir| vencord_dir(&data_dir))
+        .map(|dir| read_manifest(&manifest_path(&dir), &dir))
+        .is_ok_and(|manifest| manifest.resolve_pin(pin).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        let delays: Vec<u64> = (1..=7).map(|n| backoff_delay(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(backoff_delay(u32::MAX), MAX_BACKOFF);
+    }
+
+    fn installed(id: &str, tag: &str, installed_at: u64) -> InstalledVersion {
+        let asset = |name: &str| InstalledAsset {
+            name: name.to_string(),
+            sha256: String::new(),
+        };
+        InstalledVersion {
+            id: id.to_string(),
+            tag: tag.to_string(),
+            published_at: None,
+            installed_at,
+            preload: asset(VENCORD_PRELOAD_NAME),
+            main: asset(VENCORD_MAIN_NAME),
+        }
+    }
+
+    #[test]
+    fn install_ids_keep_only_safe_characters() {
+        assert_eq!(install_id("v1.10.4", None), "v1.10.4");
+        assert_eq!(
+            install_id("v1.10.4", Some("2024-11-02T10:00:00Z")),
+            "v1.10.4-2024-11-02T100000Z"
+        );
+        assert_eq!(install_id("../v1 /x", None), "..v1x");
+    }
+
+    #[test]
+    fn pins_resolve_by_id_or_newest_tag() {
+        let manifest = VencordManifest {
+            active: None,
+            installed: vec![
+                installed("a", "v1", 10),
+                installed("b", "v1", 20),
+                installed("c", "v2", 30),
+            ],
+        };
+        assert_eq!(manifest.resolve_pin("a").unwrap().id, "a");
+        assert_eq!(manifest.resolve_pin("v1").unwrap().id, "b");
+        assert_eq!(manifest.resolve_pin("v2").unwrap().id, "c");
+        assert!(manifest.resolve_pin("v3").is_none());
+    }
+
+    #[test]
+    fn pruning_keeps_newest_active_and_pinned() {
+        let dir = std::env::temp_dir().join(format!("ghostcord-prune-{}", std::process::id()));
+        let mut manifest = VencordManifest {
+            active: Some("v1".to_string()),
+            installed: (1..=6)
+                .map(|n| installed(&format!("v{n}"), &format!("tag{n}"), n))
+                .collect(),
+        };
+        for entry in &manifest.installed {
+            fs::create_dir_all(dir.join(&entry.id)).unwrap();
+        }
+
+        prune_installs(&dir, &mut manifest, Some("tag2"));
+        let mut kept: Vec<&str> = manifest.installed.iter().map(|v| v.id.as_str()).collect();
+        kept.sort_unstable();
+        assert_eq!(kept, ["v1", "v2", "v4", "v5", "v6"]);
+        assert!(!dir.join("v3").exists());
+        assert!(dir.join("v2").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn release_urls_encode_the_pin() {
+        let mut cfg = AppConfig::default();
+        cfg.vencord_source.base_url = Some("https://mirror.example/releases/".to_string());
+        let source = ReleaseSource::from_config(&cfg);
+        assert_eq!(
+            source.release_url(None).unwrap(),
+            "https://mirror.example/releases/latest"
+        );
+        assert_eq!(
+            source.release_url(Some("v1.2/../x?y")).unwrap(),
+            "https://mirror.example/releases/tags/v1.2%2F..%2Fx%3Fy"
+        );
+        assert!(source.release_url(Some("..")).is_err());
+    }
+
+    /// A stand-in release server: answers each connection from `files` by
+    /// path and reports the path and `Authorization` header it was asked
+    /// for.
+    fn serve(
+        files: Vec<(&'static str, String)>,
+    ) -> (String, std::sync::mpsc::Receiver<(String, Option<String>)>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, requests) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let path = line.split(' ').nth(1).unwrap_or_default().to_string();
+                let mut auth = None;
+                loop {
+                    let mut header = String::new();
+                    if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("authorization") {
+                            auth = Some(value.trim().to_string());
+                        }
+                    }
+                }
+                let _ = sender.send((path.clone(), auth));
+                let response = match files.iter().find(|(file, _)| *file == path) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (base, requests)
+    }
+
+    fn static_source(base_url: String) -> AppConfig {
+        AppConfig {
+            vencord_source: crate::config::VencordSource {
+                kind: VencordSourceKind::Static,
+                base_url: Some(base_url),
+                auth_token: Some("secret".to_string()),
+                user_agent: None,
+            },
+            vencord_download_retries: Some(0),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn installs_from_a_static_http_source() {
+        let release = |preload: &str| {
+            serde_json::json!({
+                "tag_name": "v1.0.0",
+                "published_at": null,
+                "assets": [
+                    { "name": VENCORD_PRELOAD_NAME, "browser_download_url": preload },
+                    { "name": VENCORD_MAIN_NAME, "browser_download_url": "assets/main.js" },
+                ],
+            })
+            .to_string()
+        };
+        let (base, requests) = serve(vec![
+            ("/good/latest.json", release("assets/preload.js")),
+            ("/good/assets/preload.js", "preload()".to_string()),
+            ("/good/assets/main.js", "main()".to_string()),
+            ("/evil/latest.json", release("file:///etc/hostname")),
+        ]);
+        let data_dir = std::env::temp_dir().join(format!("ghostcord-source-{}", std::process::id()));
+        let report = |_| {};
+
+        let cfg = static_source(format!("{base}/good"));
+        let assets = tauri::async_runtime::block_on(sync_assets_in(&data_dir, &cfg, &report))
+            .unwrap();
+        assert_eq!(assets.version, "v1.0.0");
+        assert_eq!(fs::read_to_string(&assets.preload_path).unwrap(), "preload()");
+        assert_eq!(fs::read_to_string(&assets.main_path).unwrap(), "main()");
+        let seen: Vec<_> = requests.try_iter().collect();
+        assert_eq!(seen.len(), 3);
+        assert!(seen
+            .iter()
+            .all(|(_, auth)| auth.as_deref() == Some("Bearer secret")));
+
+        // A remote release cannot make the downloader read local files.
+        let _ = fs::remove_dir_all(&data_dir);
+        let cfg = static_source(format!("{base}/evil"));
+        let err = tauri::async_runtime::block_on(sync_assets_in(&data_dir, &cfg, &report))
+            .unwrap_err();
+        assert!(err.contains("file://"), "{err}");
+
+        let _ = fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn guarded_stops_on_timeout_and_cancel() {
+        tauri::async_runtime::block_on(async {
+            let generation = CANCEL_GENERATION.load(Ordering::SeqCst);
+            let done = guarded(async { 7 }, Duration::from_secs(1), generation).await;
+            assert!(matches!(done, Ok(7)));
+
+            let never = std::future::pending::<()>();
+            let timed_out = guarded(never, Duration::from_millis(10), generation).await;
+            assert!(matches!(timed_out, Err(Interrupted::TimedOut)));
+
+            cancel_downloads();
+            let never = std::future::pending::<()>();
+            let cancelled = guarded(never, Duration::from_secs(60), generation).await;
+            assert!(matches!(cancelled, Err(Interrupted::Cancelled)));
+        });
+    }
+}
@@ -1,11 +1,15 @@
 use std::{error::Error, fs, path::PathBuf};
 
 use tauri::{
-    path::BaseDirectory, AppHandle, Manager, PhysicalPosition, PhysicalSize,
-    Position, Size, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent,
+    path::BaseDirectory, webview::PageLoadEvent, AppHandle, Manager, PhysicalPosition,
+    PhysicalSize, Position, Size, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent,
 };
 
-use crate::state;
+use crate::{
+    cli, deeplink, safe_mode,
+    settings::{self, SettingsStore},
+    splash, state, tray,
+};
 
 fn load_injection_script(app: &AppHandle) -> Result<String, Box<dyn Error>> {
     let mut candidates: Vec<PathBuf> = Vec::new();
@@ -54,27 +58,56 @@ fn load_injection_script(app: &AppHandle) -> Result<String, Box<dyn Error>> {
     Err("ghostcord_init.js not found in resources or dev paths".into())
 }
 
-pub fn create_main_window(app: &AppHandle) -> Result<WebviewWindow, Box<dyn Error>> {
-    let init_script = load_injection_script(app)?;
+/// Builds the Discord window hidden, opened at the launch route (a path
+/// checked by `deeplink::parse`) or `/app`; `splash` shows it once the page
+/// has loaded.
+pub fn create_main_window(
+    app: &AppHandle,
+    launch: &cli::Options,
+) -> Result<WebviewWindow, Box<dyn Error>> {
+    let mut init_script = load_injection_script(app)?;
+    // Read before any config arrives, so nothing is applied in the meantime.
+    if safe_mode::is_active(app) {
+        init_script.insert_str(0, "window.__GHOSTCORD_SAFE_MODE__ = true;\n");
+    }
+    let route = launch.route.as_deref().unwrap_or("/app");
+    let url = format!("{}{route}", deeplink::DISCORD_ORIGIN);
 
-    let window = WebviewWindowBuilder::new(
-        app,
-        "main",
-        WebviewUrl::External("https://discord.com/app".parse()?),
-    )
+    let mut builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::External(url.parse()?));
+    // Keeps the Discord login with the rest of an explicit data dir.
+    if let Some(dir) = settings::data_dir_override() {
+        builder = builder.data_directory(dir.join("webview"));
+    }
+    let window = builder
     .title("Ghostcord Lite")
     .inner_size(1100.0, 780.0)
     .resizable(true)
+    .visible(false)
     .initialization_script(init_script)
+    .on_page_load(|window, payload| {
+        if payload.event() == PageLoadEvent::Finished {
+            splash::page_loaded(window.app_handle());
+            safe_mode::page_loaded(window.app_handle());
+        }
+    })
     .build()?;
 
-    if cfg!(debug_assertions) {
+    if cfg!(debug_assertions) || launch.devtools {
         window.open_devtools();
     }
 
     Ok(window)
 }
 
+/// Brings the main window to the front, out of the tray if needed.
+pub fn show_main(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
 pub fn restore_main_window_state(
     window: &WebviewWindow,
     store: &state::StateStore,
@@ -99,6 +132,7 @@ pub fn attach_main_window_state_listeners(
 ) {
     let app_handle = app.clone();
     let window = window.clone();
+    let target = window.clone();
 
     window.on_window_event(move |event| {
         let store = app_handle.state::<state::StateStore>();
@@ -109,9 +143,15 @@ pub fn attach_main_window_state_listeners(
             WindowEvent::Resized(size) => {
                 update_bounds(&store, None, Some(*size));
             }
-            WindowEvent::CloseRequested { .. } => {
+            WindowEvent::CloseRequested { api, .. } => {
                 let app_state = store.get();
                 let _ = state::save_state(&app_handle, &store, app_state);
+
+                let close_to_tray = app_handle.state::<SettingsStore>().get().close_to_tray;
+                if close_to_tray && tray::is_available(&app_handle) {
+                    api.prevent_close();
+                    let _ = target.hide();
+                }
             }
             _ => {}
         }